@@ -1,8 +1,71 @@
-from typing import List
+from typing import Dict, List, Optional, Tuple
+
+BLENDSHAPES: List[str]
+VISEMES: List[str]
 
 
 def pose(data: List[List[float]]) -> List[List[float]]: ...
 def hand(data: List[List[float]]) -> List[List[float]]: ...
+def hand_from_pose(data: List[List[float]], left: bool) -> List[List[float]]: ...
 def hands(data: List[List[List[float]]]) -> List[List[List[float]]]: ...
-def face(data: List[List[float]]) -> List[List[float]]: ...
+def face(data: List[List[float]], matrix: Optional[List[List[float]]] = None) -> List[List[float]]: ...
+def face_matrix_deviation(data: List[List[float]], matrix: List[List[float]]) -> float: ...
+def face_blendshapes(data: List[List[float]]) -> List[float]: ...
+def face_eye_openness(data: List[List[float]]) -> List[float]: ...
+def face_brows(data: List[List[float]]) -> List[float]: ...
+def face_mouth(data: List[List[float]], calibration: Optional[Dict[str, float]] = None) -> Tuple[List[float], Optional[str]]: ...
+def face_head_pose(data: List[List[float]], reference: Optional[List[List[float]]] = None, aspect_ratio: float = 16 / 9) -> Optional[Tuple[List[float], List[float], float, float]]: ...
+def obj_vertices(text: str) -> List[List[float]]: ...
+def face_head_position(data: List[List[float]], focal_length: Optional[float] = None, aspect_ratio: float = 16 / 9) -> Tuple[List[float], float]: ...
 def holistic(data: List[List[List[float]]]) -> List[List[List[float]]]: ...
+def hand_limited(data: List[List[float]], limits: Optional[List[List[float]]] = None) -> Tuple[List[List[float]], List[bool]]: ...
+def hand_curls(data: List[List[float]], ranges: Optional[List[List[float]]] = None) -> List[List[float]]: ...
+def hand_gesture(data: List[List[float]]) -> List[float]: ...
+def export_bvh(poses: List[List[List[float]]], left_hands: Optional[List[List[List[float]]]] = None, right_hands: Optional[List[List[List[float]]]] = None, fps: float = 30.0, order: str = "ZXY", scale: float = 1.0) -> str: ...
+def export_gltf(path: str, poses: List[List[List[float]]], left_hands: Optional[List[List[List[float]]]] = None, right_hands: Optional[List[List[List[float]]]] = None, fps: float = 30.0, scale: float = 1.0) -> None: ...
+def export_collada(poses: List[List[List[float]]], left_hands: Optional[List[List[List[float]]]] = None, right_hands: Optional[List[List[List[float]]]] = None, fps: float = 30.0, scale: float = 1.0) -> str: ...
+def export_usd(poses: List[List[List[float]]], left_hands: Optional[List[List[List[float]]]] = None, right_hands: Optional[List[List[List[float]]]] = None, faces: Optional[List[List[List[float]]]] = None, fps: float = 30.0, scale: float = 1.0) -> str: ...
+def export_maya_anim(poses: List[List[List[float]]], left_hands: Optional[List[List[List[float]]]] = None, right_hands: Optional[List[List[List[float]]]] = None, faces: Optional[List[List[List[float]]]] = None, fps: float = 30.0, order: str = "ZYX", scale: float = 1.0, tolerance: Optional[float] = None) -> str: ...
+def export_trc(poses: List[List[List[float]]], fps: float = 30.0, window: Optional[int] = None, name: str = "mediapipe.trc") -> str: ...
+def export_mot(poses: List[List[List[float]]], fps: float = 30.0, window: Optional[int] = None, name: str = "mediapipe.mot") -> str: ...
+def export_c3d(path: str, poses: List[List[List[float]]], visibility: Optional[List[List[float]]] = None, fps: float = 30.0) -> None: ...
+def read_landmarks_csv(text: str) -> Tuple[List[List[List[float]]], List[List[float]]]: ...
+def write_landmarks_csv(landmarks: List[List[List[float]]], visibility: Optional[List[List[float]]] = None) -> str: ...
+def read_session(path: str) -> List[Tuple[float, Optional[List[List[float]]], Optional[List[List[float]]], Optional[List[List[float]]], Optional[List[List[float]]]]]: ...
+def write_session(path: str, frames: List[Tuple[float, Optional[List[List[float]]], Optional[List[List[float]]], Optional[List[List[float]]], Optional[List[List[float]]]]]) -> None: ...
+def rotations_csv(pose: Optional[List[List[List[float]]]] = None, left_hand: Optional[List[List[List[float]]]] = None, right_hand: Optional[List[List[List[float]]]] = None, face: Optional[List[List[List[float]]]] = None, order: Optional[str] = None) -> str: ...
+def evaluate_bvh(poses: List[List[List[float]]], reference: str, fps: float = 30.0, time_offset: float = 0.0, bone_map: Optional[Dict[str, str]] = None) -> Tuple[float, Dict[str, List[float]], str]: ...
+
+
+class GestureTracker:
+    def __init__(self, threshold: float = 0.6, hold_frames: int = 3) -> None: ...
+    def update(self, data: List[List[float]]) -> Optional[str]: ...
+    def scores(self) -> List[float]: ...
+    def reset(self) -> None: ...
+
+
+class EyeTracker:
+    def __init__(self, rise_rate: float = 0.5, fall_rate: float = 0.01) -> None: ...
+    def update(self, data: List[List[float]]) -> List[float]: ...
+    def baseline(self) -> List[float]: ...
+    def reset(self) -> None: ...
+
+
+class NeutralFace:
+    def __init__(self) -> None: ...
+    def add_frame(self, data: List[List[float]]) -> None: ...
+    def frames(self) -> int: ...
+    def rotations(self, data: List[List[float]]) -> List[List[float]]: ...
+    def blendshapes(self, data: List[List[float]]) -> List[float]: ...
+    def eye_openness(self, data: List[List[float]]) -> List[float]: ...
+    def brows(self, data: List[List[float]]) -> List[float]: ...
+    def mouth(self, data: List[List[float]]) -> Tuple[List[float], str]: ...
+    def serialize(self) -> str: ...
+    @staticmethod
+    def deserialize(text: str) -> "NeutralFace": ...
+
+
+class SessionWriter:
+    def __init__(self, path: str) -> None: ...
+    def write(self, timestamp: float, pose: Optional[List[List[float]]] = None, face: Optional[List[List[float]]] = None, left_hand: Optional[List[List[float]]] = None, right_hand: Optional[List[List[float]]] = None) -> None: ...
+    def flush(self) -> None: ...
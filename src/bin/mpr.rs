@@ -0,0 +1,264 @@
+use cgt_math::Quaternion;
+use mediapipe_rotations::skeleton::{self, Frame, RotationOrder};
+use mediapipe_rotations::{bvh, c3d, collada, csv, eval, gltf, hand, maya, opensim, pose, session, usd};
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::process;
+
+const USAGE: &str = "Usage: mpr <command> <input> <output> [options]
+
+Commands:
+    bvh     Export the pose sequence as BVH
+    dae     Export the pose sequence as Collada
+    usda    Export the pose sequence as UsdSkel animation
+    anim    Export the pose sequence as Maya .anim curves
+    trc     Export the pose landmarks as OpenSim marker file
+    mot     Export the joint angles as OpenSim motion file
+    mprs    Convert the input to a recorded session with timestamps from the frame rate
+    c3d     Export the pose landmarks as C3D points with the joint angles as analog channels
+    csv     Export the solved pose and hand rotations as CSV
+    gltf    Export the pose sequence as .gltf with .bin or as .glb depending on the output extension
+    eval    Compare the pose sequence with the reference BVH passed as output and print a report
+
+Input:
+    JSON lines, one frame per line: {\"pose\": [[x, y, z], ...], \"left_hand\": [...], \"right_hand\": [...], \"face\": [...]}
+    or pose landmarks as .csv with frame, landmark, x, y, z, visibility columns
+    or a recorded .mprs session
+
+Options:
+    --fps <fps>         Frame rate (default 30, sessions use their timestamps)
+    --order <order>     Euler rotation order (default ZXY, ZYX for anim)
+    --scale <scale>     Scale of offsets and positions (default 1)
+    --hands             Include finger bones
+    --euler             Write euler angles in the rotation order instead of quaternions to CSV
+    --offset <seconds>  Time offset of the reference recording (default 0)
+    --tolerance <value> Reduce .anim keys to the tolerance in degrees and units
+    --window <frames>   Smooth the pose landmarks with a moving average";
+
+struct Options {
+    fps: Option<f32>,
+    order: Option<RotationOrder>,
+    scale: f32,
+    hands: bool,
+    euler: bool,
+    offset: f32,
+    tolerance: Option<f32>,
+    window: Option<usize>,
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if let Err(err) = run(&args) {
+        eprintln!("{}\n\n{}", err, USAGE);
+        process::exit(1);
+    }
+}
+
+fn run(args: &[String]) -> Result<(), String> {
+    if args.len() < 3 {
+        return Err(String::from("Missing arguments."));
+    }
+    let options = parse_options(&args[3..])?;
+    // json lines have no visibility
+    let input = args[1].to_lowercase();
+    let mut fps = options.fps;
+    let (mut frames, visibility): (Vec<Frame>, Vec<Vec<f32>>) = if input.ends_with(".mprs") {
+        let recorded = session::load(Path::new(&args[1]))?;
+        fps = fps.or_else(|| session::frame_rate(&recorded));
+        let visibility = recorded.iter().map(|x| x.pose.as_ref().map(|x| x.visibility.clone()).unwrap_or_default()).collect();
+        (recorded.iter().map(|x| x.to_frame()).collect(), visibility)
+    }
+    else {
+        let text = fs::read_to_string(&args[1]).map_err(|err| format!("Failed to read {}: {}", args[1], err))?;
+        if input.ends_with(".csv") {
+            let landmarks = csv::read_landmarks(&text)?;
+            (landmarks.landmarks.into_iter().map(|pose| Frame { pose, ..Frame::default() }).collect(), landmarks.visibility)
+        }
+        else {
+            (read_frames(&text)?, Vec::new())
+        }
+    };
+    let fps = fps.unwrap_or(30.0);
+    if let Some(window) = options.window {
+        let poses: Vec<Vec<[f32; 3]>> = frames.iter().map(|x| x.pose.clone()).collect();
+        for (frame, pose) in frames.iter_mut().zip(opensim::smooth(&poses, window)) {
+            frame.pose = pose;
+        }
+    }
+    let animation = skeleton::animate(&frames, fps, options.hands);
+    let poses: Vec<Vec<[f32; 3]>> = frames.iter().map(|x| x.pose.clone()).collect();
+    let name = Path::new(&args[2]).file_name().and_then(|x| x.to_str()).unwrap_or("mediapipe");
+
+    let result = match args[0].as_str() {
+        "bvh" => bvh::write(&animation, options.order.unwrap_or(RotationOrder::ZXY), options.scale),
+        "dae" => collada::write(&animation, options.scale),
+        "usda" => usd::write(&animation, options.scale),
+        "anim" => maya::write(&animation, options.order.unwrap_or(RotationOrder::ZYX), options.scale, options.tolerance),
+        "trc" => opensim::write_trc(&poses, fps, name),
+        "mot" => opensim::write_mot(&animation, name),
+        "csv" => csv::write_rotations(&solve_rotations(&frames, options.hands), if options.euler { Some(options.order.unwrap_or(RotationOrder::ZXY)) } else { None }),
+        "mprs" => {
+            let recorded: Vec<session::SessionFrame> = frames.iter().enumerate().map(|(i, frame)| to_session_frame(i as f64 / fps as f64, frame, visibility.get(i))).collect();
+            return session::save(Path::new(&args[2]), &recorded).map_err(|err| format!("Failed to write {}: {}", args[2], err));
+        },
+        "c3d" => {
+            let c3d = c3d::write(&poses, &visibility, &animation)?;
+            return fs::write(&args[2], c3d).map_err(|err| format!("Failed to write {}: {}", args[2], err));
+        },
+        "gltf" => {
+            let path = Path::new(&args[2]);
+            return gltf::save(path, &animation, options.scale).map_err(|err| format!("Failed to write {}: {}", args[2], err));
+        },
+        "eval" => {
+            let text = fs::read_to_string(&args[2]).map_err(|err| format!("Failed to read {}: {}", args[2], err))?;
+            let reference = bvh::parse(&text)?;
+            let bone_map = eval::default_bone_map(&reference.skeleton);
+            let report = eval::evaluate(&animation, &reference, &bone_map, options.offset)?;
+            print!("{}", report.to_text());
+            return Ok(());
+        },
+        command => return Err(format!("Unknown command {}.", command)),
+    };
+    return fs::write(&args[2], result).map_err(|err| format!("Failed to write {}: {}", args[2], err));
+}
+
+/// Session frame of the landmarks, empty landmark lists are not detected.
+fn to_session_frame(timestamp: f64, frame: &Frame, pose_visibility: Option<&Vec<f32>>) -> session::SessionFrame {
+    let detection = |landmarks: &Vec<[f32; 3]>, visibility: Option<&Vec<f32>>| {
+        if landmarks.is_empty() {
+            return None;
+        }
+        Some(session::Detection { landmarks: landmarks.clone(), visibility: visibility.cloned().unwrap_or_default() })
+    };
+    return session::SessionFrame {
+        timestamp,
+        pose: detection(&frame.pose, pose_visibility),
+        face: detection(&frame.face, None),
+        left_hand: detection(&frame.left_hand, None),
+        right_hand: detection(&frame.right_hand, None),
+    };
+}
+
+/// Named solver rotations per frame.
+fn solve_rotations(frames: &[Frame], hands: bool) -> Vec<Vec<(String, Quaternion)>> {
+    let pose_names = csv::pose_names();
+    let mut result: Vec<Vec<(String, Quaternion)>> = Vec::new();
+    for frame in frames.iter() {
+        let mut rotations: Vec<(String, Quaternion)> = Vec::new();
+        if frame.pose.len() == 33 {
+            rotations.extend(csv::named_rotations(&pose_names, &pose::main(&frame.pose), ""));
+        }
+        for (data, suffix) in [(&frame.left_hand, ".L"), (&frame.right_hand, ".R")] {
+            if hands && data.len() == 21 {
+                rotations.extend(csv::named_rotations(&skeleton::HAND_BONES, &hand::main(data), suffix));
+            }
+        }
+        result.push(rotations);
+    }
+    return result;
+}
+
+fn parse_options(args: &[String]) -> Result<Options, String> {
+    let mut options = Options { fps: None, order: None, scale: 1.0, hands: false, euler: false, offset: 0.0, tolerance: None, window: None };
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let mut value = || iter.next().ok_or(format!("Missing value for {}.", arg));
+        match arg.as_str() {
+            "--fps" => options.fps = Some(parse_positive(value()?)?),
+            "--scale" => options.scale = parse_number(value()?)?,
+            "--offset" => options.offset = parse_number(value()?)?,
+            "--tolerance" => options.tolerance = Some(parse_number(value()?)?),
+            "--window" => options.window = Some(parse_window(value()?)?),
+            "--order" => options.order = Some(value()?.parse()?),
+            "--hands" => options.hands = true,
+            "--euler" => options.euler = true,
+            _ => return Err(format!("Unknown option {}.", arg)),
+        }
+    }
+    return Ok(options);
+}
+
+fn parse_number(value: &str) -> Result<f32, String> {
+    return value.parse::<f32>().map_err(|_| format!("Invalid number {}.", value));
+}
+
+fn parse_positive(value: &str) -> Result<f32, String> {
+    let number = parse_number(value)?;
+    if !number.is_finite() || number <= 0.0 {
+        return Err(format!("Expected a positive number, got {}.", value));
+    }
+    return Ok(number);
+}
+
+fn parse_window(value: &str) -> Result<usize, String> {
+    return match value.parse::<usize>() {
+        Ok(window) if window > 0 => Ok(window),
+        _ => Err(format!("Invalid window {}, expected a positive number of frames.", value)),
+    };
+}
+
+/// Reads one frame per line, missing or empty landmark lists are treated as not detected.
+fn read_frames(text: &str) -> Result<Vec<Frame>, String> {
+    let mut frames: Vec<Frame> = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let landmarks = |key: &str| landmark_list(line, key).map_err(|err| format!("Line {}: {}", i + 1, err));
+        frames.push(Frame {
+            pose: landmarks("pose")?,
+            left_hand: landmarks("left_hand")?,
+            right_hand: landmarks("right_hand")?,
+            face: landmarks("face")?,
+        });
+    }
+    return Ok(frames);
+}
+
+/// Parses the list of landmarks following the key, every landmark is a list of x, y, z and an optional visibility.
+fn landmark_list(line: &str, key: &str) -> Result<Vec<[f32; 3]>, String> {
+    let start = match line.find(&format!("\"{}\"", key)) {
+        Some(start) => start + key.len() + 2,
+        None => return Ok(Vec::new()),
+    };
+    let rest = line[start..].trim_start().trim_start_matches(':').trim_start();
+    if rest.starts_with("null") {
+        return Ok(Vec::new());
+    }
+    if !rest.starts_with('[') {
+        return Err(format!("Expected a list for {}.", key));
+    }
+    let mut depth = 0;
+    let mut end = None;
+    for (i, c) in rest.char_indices() {
+        match c {
+            '[' => depth += 1,
+            ']' => {
+                depth -= 1;
+                if depth == 0 {
+                    end = Some(i);
+                    break;
+                }
+            },
+            _ => {},
+        }
+    }
+    let end = end.ok_or(format!("Unterminated list for {}.", key))?;
+    let mut landmarks: Vec<[f32; 3]> = Vec::new();
+    for part in rest[1..end].split(']') {
+        let part = part.trim().trim_start_matches(',').trim();
+        if part.is_empty() {
+            continue;
+        }
+        let values = part.strip_prefix('[').ok_or(format!("Expected a list per landmark for {}.", key))?;
+        let numbers = values.split(',')
+            .map(|x| parse_number(x.trim()))
+            .collect::<Result<Vec<f32>, String>>()?;
+        if numbers.len() != 3 && numbers.len() != 4 {
+            return Err(format!("Expected 3 or 4 components per landmark for {}, got {}.", key, numbers.len()));
+        }
+        landmarks.push([numbers[0], numbers[1], numbers[2]]);
+    }
+    return Ok(landmarks);
+}
@@ -0,0 +1,246 @@
+extern crate cgt_math;
+use cgt_math::{Quaternion, Vector3};
+use crate::skeleton::{self, Animation, Bone, RotationOrder, Skeleton};
+use std::fmt::Write;
+
+/// Writes the animation as BVH motion capture file.
+/// Offsets and root positions are multiplied by scale, rotations are written in degrees.
+pub fn write(animation: &Animation, order: RotationOrder, scale: f32) -> String {
+    let mut text = String::from("HIERARCHY\n");
+    let bones = &animation.skeleton.bones;
+    let axes = order.axes();
+    let channels: Vec<String> = axes.iter().map(|x| format!("{}rotation", ["X", "Y", "Z"][*x])).collect();
+    let channels = channels.join(" ");
+
+    // depth first order, the motion data follows the order of the hierarchy
+    let mut order_idx: Vec<usize> = Vec::new();
+    for (i, bone) in bones.iter().enumerate() {
+        if bone.parent.is_none() {
+            write_joint(&mut text, animation, i, 0, &channels, scale, &mut order_idx);
+        }
+    }
+
+    writeln!(text, "MOTION").unwrap();
+    writeln!(text, "Frames: {}", animation.frame_count()).unwrap();
+    writeln!(text, "Frame Time: {:.6}", animation.frame_time).unwrap();
+    for (frame, rotations) in animation.rotations.iter().enumerate() {
+        let mut values: Vec<String> = Vec::new();
+        let root = animation.root_positions[frame];
+        for x in [root.x, root.y, root.z] {
+            values.push(format!("{:.6}", x * scale));
+        }
+        for idx in order_idx.iter() {
+            let angles = skeleton::to_euler(rotations[*idx], order);
+            for axis in axes.iter() {
+                values.push(format!("{:.6}", angles[*axis].to_degrees()));
+            }
+        }
+        writeln!(text, "{}", values.join(" ")).unwrap();
+    }
+    return text;
+}
+
+fn write_joint(text: &mut String, animation: &Animation, idx: usize, depth: usize, channels: &str, scale: f32, order_idx: &mut Vec<usize>) {
+    let bone = &animation.skeleton.bones[idx];
+    let indent = "\t".repeat(depth);
+    order_idx.push(idx);
+    if depth == 0 {
+        writeln!(text, "ROOT {}", bone.name).unwrap();
+    }
+    else {
+        writeln!(text, "{}JOINT {}", indent, bone.name).unwrap();
+    }
+    writeln!(text, "{}{{", indent).unwrap();
+    writeln!(text, "{}\tOFFSET {}", indent, format_vec(bone.offset, scale)).unwrap();
+    if depth == 0 {
+        writeln!(text, "{}\tCHANNELS 6 Xposition Yposition Zposition {}", indent, channels).unwrap();
+    }
+    else {
+        writeln!(text, "{}\tCHANNELS 3 {}", indent, channels).unwrap();
+    }
+
+    let children = animation.skeleton.children(idx);
+    for child in children.iter() {
+        write_joint(text, animation, *child, depth + 1, channels, scale, order_idx);
+    }
+    if children.is_empty() {
+        let end = bone.end.unwrap_or(Vector3::ZERO);
+        writeln!(text, "{}\tEnd Site", indent).unwrap();
+        writeln!(text, "{}\t{{", indent).unwrap();
+        writeln!(text, "{}\t\tOFFSET {}", indent, format_vec(end, scale)).unwrap();
+        writeln!(text, "{}\t}}", indent).unwrap();
+    }
+    writeln!(text, "{}}}", indent).unwrap();
+}
+
+/// Reads a BVH motion capture file.
+/// Rotation channels are composed in the listed order, position channels are only used for the root joint.
+pub fn parse(text: &str) -> Result<Animation, String> {
+    let mut tokens = text.split_whitespace();
+    if tokens.next() != Some("HIERARCHY") {
+        return Err(String::from("Expected HIERARCHY."));
+    }
+
+    // bones and their channels as (position, axis)
+    let mut bones: Vec<Bone> = Vec::new();
+    let mut channels: Vec<Vec<(bool, usize)>> = Vec::new();
+    let mut stack: Vec<Option<usize>> = Vec::new();
+    let mut parent: Option<usize> = None;
+    loop {
+        let token = tokens.next().ok_or("Unexpected end of hierarchy.")?;
+        match token {
+            "ROOT" | "JOINT" => {
+                if token == "ROOT" && !bones.is_empty() {
+                    return Err(String::from("Expected a single root joint."));
+                }
+                let name = tokens.next().ok_or("Missing joint name.")?;
+                expect(tokens.next(), "{")?;
+                bones.push(Bone { name: name.to_string(), parent, offset: Vector3::ZERO, end: None });
+                channels.push(Vec::new());
+                stack.push(parent);
+                parent = Some(bones.len() - 1);
+            },
+            "End" => {
+                expect(tokens.next(), "Site")?;
+                expect(tokens.next(), "{")?;
+                expect(tokens.next(), "OFFSET")?;
+                let end = read_vec(&mut tokens)?;
+                expect(tokens.next(), "}")?;
+                if let Some(idx) = parent {
+                    bones[idx].end = Some(end);
+                }
+            },
+            "OFFSET" => {
+                let idx = parent.ok_or("OFFSET outside of a joint.")?;
+                bones[idx].offset = read_vec(&mut tokens)?;
+            },
+            "CHANNELS" => {
+                let idx = parent.ok_or("CHANNELS outside of a joint.")?;
+                let count = read_count(tokens.next())?;
+                for _ in 0..count {
+                    let channel = tokens.next().ok_or("Missing channel.")?;
+                    let axis = match channel.chars().next() {
+                        Some('X') => 0,
+                        Some('Y') => 1,
+                        Some('Z') => 2,
+                        _ => return Err(format!("Invalid channel {}.", channel)),
+                    };
+                    channels[idx].push((channel.ends_with("position"), axis));
+                }
+            },
+            "}" => {
+                parent = stack.pop().ok_or("Unbalanced braces.")?;
+                if stack.is_empty() {
+                    break;
+                }
+            },
+            _ => return Err(format!("Unexpected token {}.", token)),
+        }
+    }
+
+    expect(tokens.next(), "MOTION")?;
+    expect(tokens.next(), "Frames:")?;
+    let frame_count = read_count(tokens.next())?;
+    expect(tokens.next(), "Frame")?;
+    expect(tokens.next(), "Time:")?;
+    let frame_time = read_number(tokens.next())?;
+    if !frame_time.is_finite() || frame_time <= 0.0 {
+        return Err(format!("Invalid frame time {}.", frame_time));
+    }
+
+    // the frame count is only trusted as far as the frames are actually present
+    let mut root_positions: Vec<Vector3> = Vec::new();
+    let mut rotations: Vec<Vec<Quaternion>> = Vec::new();
+    let offset = bones.first().map(|x| x.offset).unwrap_or(Vector3::ZERO);
+    for _ in 0..frame_count {
+        let mut root = [offset.x, offset.y, offset.z];
+        let mut frame: Vec<Quaternion> = Vec::with_capacity(bones.len());
+        for (i, bone_channels) in channels.iter().enumerate() {
+            let mut q = Quaternion::IDENTITY;
+            for (position, axis) in bone_channels.iter() {
+                let value = read_number(tokens.next())?;
+                if !*position {
+                    q *= skeleton::axis_rotation(*axis, value.to_radians());
+                }
+                else if i == 0 {
+                    root[*axis] += value;
+                }
+            }
+            frame.push(q);
+        }
+        root_positions.push(Vector3::from_array(root));
+        rotations.push(frame);
+    }
+
+    return Ok(Animation { skeleton: Skeleton { bones }, frame_time, root_positions, rotations });
+}
+
+fn expect(token: Option<&str>, expected: &str) -> Result<(), String> {
+    match token {
+        Some(x) if x == expected => return Ok(()),
+        Some(x) => return Err(format!("Expected {} but found {}.", expected, x)),
+        None => return Err(format!("Expected {} but reached the end.", expected)),
+    }
+}
+
+fn read_number(token: Option<&str>) -> Result<f32, String> {
+    let token = token.ok_or("Unexpected end of file.")?;
+    return token.parse::<f32>().map_err(|_| format!("Invalid number {}.", token));
+}
+
+fn read_count(token: Option<&str>) -> Result<usize, String> {
+    let token = token.ok_or("Unexpected end of file.")?;
+    return token.parse::<usize>().map_err(|_| format!("Invalid count {}.", token));
+}
+
+fn read_vec<'a>(tokens: &mut impl Iterator<Item = &'a str>) -> Result<Vector3, String> {
+    return Ok(Vector3::new(read_number(tokens.next())?, read_number(tokens.next())?, read_number(tokens.next())?));
+}
+
+fn format_vec(vec: Vector3, scale: f32) -> String {
+    return format!("{:.6} {:.6} {:.6}", vec.x * scale, vec.y * scale, vec.z * scale);
+}
+
+#[cfg(test)]
+mod test {
+    use cgt_math::Vector3;
+    use crate::skeleton::{self, RotationOrder};
+    #[test]
+    fn impl_test() {
+        let frames = vec![skeleton::Frame::default(); 2];
+        let animation = skeleton::animate(&frames, 25.0, false);
+        let text = super::write(&animation, RotationOrder::ZXY, 100.0);
+
+        assert!(text.starts_with("HIERARCHY\nROOT hips\n"));
+        assert!(text.contains("CHANNELS 6 Xposition Yposition Zposition Zrotation Xrotation Yrotation"));
+        assert!(text.contains("Frames: 2\nFrame Time: 0.040000\n"));
+        assert_eq!(text.matches("JOINT").count(), 13);
+        assert_eq!(text.matches("End Site").count(), 4);
+
+        // root position and three channels per bone
+        let last = text.lines().last().unwrap();
+        assert_eq!(last.split(' ').count(), 3 + 14 * 3);
+
+        // round trip
+        let mut animation = animation;
+        animation.rotations[1][3] = skeleton::from_euler([0.3, -0.2, 0.1], RotationOrder::XYZ);
+        animation.root_positions[1] = Vector3::new(0.1, 0.9, -0.2);
+        let parsed = super::parse(&super::write(&animation, RotationOrder::YZX, 1.0)).unwrap();
+        assert_eq!(parsed.frame_count(), 2);
+        assert_eq!(parsed.skeleton.bones.len(), 14);
+        assert_eq!(parsed.skeleton.bones[3].name, animation.skeleton.bones[3].name);
+        assert!((parsed.frame_time - 0.04).abs() < 1e-6);
+        assert!(parsed.root_positions[1].distance_to(animation.root_positions[1]) < 1e-5);
+        let (a, b) = (parsed.rotations[1][3].to_array(), animation.rotations[1][3].to_array());
+        let dot: f32 = (0..4).map(|i| a[i] * b[i]).sum();
+        assert!((dot.abs() - 1.0).abs() < 1e-5);
+        assert!(super::parse("HIERARCHY\nROOT hips\n{").is_err());
+
+        // invalid frame counts and frame times
+        let text = super::write(&animation, RotationOrder::YZX, 1.0);
+        for (from, to) in [("Frames: 2", "Frames: -2"), ("Frames: 2", "Frames: 2.5"), ("Frames: 2", "Frames: inf"),
+            ("Frames: 2", "Frames: 4000000000"), ("Frame Time: 0.040000", "Frame Time: 0"), ("Frame Time: 0.040000", "Frame Time: NaN")] {
+            assert!(super::parse(&text.replace(from, to)).is_err());
+        }
+    }
+}
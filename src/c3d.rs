@@ -0,0 +1,230 @@
+use crate::opensim::{self, MARKER_NAMES};
+use crate::skeleton::Animation;
+
+const BLOCK: usize = 512;
+const INTEL: u8 = 84;
+const CHAR: i8 = -1;
+const INT: i8 = 2;
+const FLOAT: i8 = 4;
+
+/// Parameter data as type, dimensions and little endian bytes.
+struct Parameter {
+    name: &'static str,
+    kind: i8,
+    dimensions: Vec<u8>,
+    data: Vec<u8>,
+}
+
+/// Writes the pose landmarks as C3D points and the solved joint angles as analog channels.
+/// Points are in millimeters in a Z-up lab frame, x points towards the camera and y to the left of the subject facing it.
+/// The residual of a point is (1 - visibility) * 100 mm but at least 1 as 0 marks interpolated points,
+/// landmarks of frames without detection are invalid.
+/// The analog channels are named after the OpenSim coordinates, sampled once per frame with a scale of 1.
+/// The animation has to be solved from the poses, fails if the frame counts differ.
+pub fn write(poses: &[Vec<[f32; 3]>], visibility: &[Vec<f32>], animation: &Animation) -> Result<Vec<u8>, String> {
+    let frame_count = poses.len();
+    if animation.frame_count() != frame_count {
+        return Err(format!("Expected an animation of {} frames, got {}.", frame_count, animation.frame_count()));
+    }
+    let fps = (1000.0 / animation.frame_time).round() / 1000.0;
+    let point_count = MARKER_NAMES.len();
+    let analog_count = opensim::COORDINATES.len();
+    let coordinates = opensim::coordinates(animation);
+
+    let groups: [(i8, &str, &str, Vec<Parameter>); 3] = [
+        (1, "POINT", "3D point parameters", vec![
+            int("USED", &[point_count as u16]),
+            float("SCALE", &[-1.0]),
+            float("RATE", &[fps]),
+            int("DATA_START", &[0]),
+            int("FRAMES", &[frame_count.min(u16::MAX as usize) as u16]),
+            chars("LABELS", &MARKER_NAMES),
+            chars("DESCRIPTIONS", &[""; 33]),
+            chars("UNITS", &["mm"]),
+            chars("X_SCREEN", &["+X"]),
+            chars("Y_SCREEN", &["+Z"]),
+        ]),
+        (2, "ANALOG", "Solved joint angles", vec![
+            int("USED", &[analog_count as u16]),
+            chars("LABELS", &opensim::COORDINATES),
+            chars("DESCRIPTIONS", &vec!["OpenSim coordinate"; analog_count]),
+            float("GEN_SCALE", &[1.0]),
+            float("SCALE", &vec![1.0; analog_count]),
+            int("OFFSET", &vec![0; analog_count]),
+            chars("UNITS", &opensim::COORDINATES.map(|x| if x.ends_with("_tx") || x.ends_with("_ty") || x.ends_with("_tz") { "m" } else { "deg" })),
+            float("RATE", &[fps]),
+        ]),
+        (3, "TRIAL", "Trial parameters", vec![
+            int("ACTUAL_START_FIELD", &[1, 0]),
+            int("ACTUAL_END_FIELD", &[(frame_count & 0xFFFF) as u16, (frame_count >> 16) as u16]),
+        ]),
+    ];
+
+    // parameter records, the data start is patched once the size of the parameter section is known
+    let mut parameters: Vec<u8> = vec![0x01, 0x50, 0, INTEL];
+    let mut data_start_offset = 0;
+    for (id, name, description, _) in groups.iter() {
+        push_record(&mut parameters, name, -id, &[], description);
+    }
+    for (id, _, _, group) in groups.iter() {
+        for parameter in group.iter() {
+            let mut body: Vec<u8> = vec![parameter.kind as u8, parameter.dimensions.len() as u8];
+            body.extend_from_slice(&parameter.dimensions);
+            if parameter.name == "DATA_START" {
+                data_start_offset = parameters.len() + 2 + parameter.name.len() + 2 + body.len();
+            }
+            body.extend_from_slice(&parameter.data);
+            push_record(&mut parameters, parameter.name, *id, &body, "");
+        }
+    }
+    // the last record points to no following record
+    let last = last_record_offset(&parameters);
+    parameters[last..last + 2].copy_from_slice(&0i16.to_le_bytes());
+    let parameter_blocks = parameters.len().div_ceil(BLOCK);
+    parameters[2] = parameter_blocks as u8;
+    parameters.resize(parameter_blocks * BLOCK, 0);
+    let data_start = 2 + parameter_blocks;
+    parameters[data_start_offset..data_start_offset + 2].copy_from_slice(&(data_start as u16).to_le_bytes());
+
+    let mut header = vec![0u8; BLOCK];
+    let words: [u16; 6] = [0x5002, point_count as u16, analog_count as u16, 1, frame_count.clamp(1, u16::MAX as usize) as u16, 10];
+    for (i, word) in words.iter().enumerate() {
+        header[i * 2..i * 2 + 2].copy_from_slice(&word.to_le_bytes());
+    }
+    header[12..16].copy_from_slice(&(-1.0f32).to_le_bytes());
+    header[16..18].copy_from_slice(&(data_start as u16).to_le_bytes());
+    header[18..20].copy_from_slice(&1u16.to_le_bytes());
+    header[20..24].copy_from_slice(&fps.to_le_bytes());
+
+    let mut data: Vec<u8> = Vec::with_capacity(frame_count * (point_count * 4 + analog_count) * 4);
+    for (frame, pose) in poses.iter().enumerate() {
+        for i in 0..point_count {
+            match pose.get(i) {
+                Some(position) if position.iter().all(|x| x.is_finite()) => {
+                    let visibility = visibility.get(frame).and_then(|x| x.get(i)).copied().unwrap_or(1.0);
+                    let residual = ((1.0 - visibility) * 100.0).clamp(1.0, 255.0).round();
+                    for value in to_lab_space(*position).iter().chain([residual].iter()) {
+                        data.extend_from_slice(&value.to_le_bytes());
+                    }
+                },
+                _ => {
+                    for value in [0.0f32, 0.0, 0.0, -1.0] {
+                        data.extend_from_slice(&value.to_le_bytes());
+                    }
+                },
+            }
+        }
+        for value in coordinates[frame].iter() {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+    }
+    data.resize(data.len().div_ceil(BLOCK) * BLOCK, 0);
+
+    let mut result = header;
+    result.extend_from_slice(&parameters);
+    result.extend_from_slice(&data);
+    return Ok(result);
+}
+
+/// Converts MediaPipe world coordinates in meters to the Z-up lab frame in millimeters.
+fn to_lab_space(v: [f32; 3]) -> [f32; 3] {
+    return [-v[2] * 1000.0, v[0] * 1000.0, -v[1] * 1000.0];
+}
+
+/// Appends a group or parameter record with the offset to the following record.
+fn push_record(buffer: &mut Vec<u8>, name: &str, id: i8, body: &[u8], description: &str) {
+    buffer.push(name.len() as u8);
+    buffer.push(id as u8);
+    buffer.extend_from_slice(name.as_bytes());
+    let next = (2 + body.len() + 1 + description.len()) as i16;
+    buffer.extend_from_slice(&next.to_le_bytes());
+    buffer.extend_from_slice(body);
+    buffer.push(description.len() as u8);
+    buffer.extend_from_slice(description.as_bytes());
+}
+
+/// Position of the next record offset of the last record.
+fn last_record_offset(parameters: &[u8]) -> usize {
+    let mut position = 4;
+    let mut last = 4;
+    while position < parameters.len() {
+        let name_length = parameters[position] as usize;
+        last = position + 2 + name_length;
+        let next = i16::from_le_bytes([parameters[last], parameters[last + 1]]) as usize;
+        position = last + next;
+    }
+    return last;
+}
+
+fn int(name: &'static str, values: &[u16]) -> Parameter {
+    let dimensions = if values.len() == 1 { Vec::new() } else { vec![values.len() as u8] };
+    return Parameter { name, kind: INT, dimensions, data: values.iter().flat_map(|x| x.to_le_bytes()).collect() };
+}
+
+fn float(name: &'static str, values: &[f32]) -> Parameter {
+    let dimensions = if values.len() == 1 { Vec::new() } else { vec![values.len() as u8] };
+    return Parameter { name, kind: FLOAT, dimensions, data: values.iter().flat_map(|x| x.to_le_bytes()).collect() };
+}
+
+/// Character array padded to the longest entry.
+fn chars(name: &'static str, values: &[&str]) -> Parameter {
+    let length = values.iter().map(|x| x.len()).max().unwrap_or(0).max(1);
+    let mut data: Vec<u8> = Vec::with_capacity(length * values.len());
+    for value in values.iter() {
+        data.extend_from_slice(value.as_bytes());
+        data.resize(data.len() + length - value.len(), b' ');
+    }
+    let dimensions = if values.len() == 1 { vec![length as u8] } else { vec![length as u8, values.len() as u8] };
+    return Parameter { name, kind: CHAR, dimensions, data };
+}
+
+#[cfg(test)]
+mod test {
+    use crate::skeleton;
+    #[test]
+    fn impl_test() {
+        let poses = vec![vec![[0.1, -0.5, 0.2]; 33], Vec::new(), vec![[0.1, -0.5, 0.2]; 33]];
+        let visibility = vec![vec![0.25; 33], Vec::new(), Vec::new()];
+        let frames: Vec<skeleton::Frame> = poses.iter().map(|x| skeleton::Frame { pose: x.clone(), ..skeleton::Frame::default() }).collect();
+        let animation = skeleton::animate(&frames, 50.0, false);
+        let c3d = super::write(&poses, &visibility, &animation).unwrap();
+        let word = |i: usize| u16::from_le_bytes([c3d[i * 2], c3d[i * 2 + 1]]);
+        let float = |i: usize| f32::from_le_bytes([c3d[i], c3d[i + 1], c3d[i + 2], c3d[i + 3]]);
+
+        assert_eq!(c3d.len() % 512, 0);
+        assert_eq!([word(0), word(1), word(2), word(3), word(4)], [0x5002, 33, 25, 1, 3]);
+        assert_eq!(float(20), 50.0);
+        assert_eq!(c3d[512 + 1], 0x50);
+        assert_eq!(c3d[512 + 3], 84);
+        let data_start = (word(8) as usize - 1) * 512;
+        assert_eq!(data_start, 512 + c3d[512 + 2] as usize * 512);
+
+        // the parameter records chain to the end and the data start parameter matches the header
+        let mut position = 512 + 4;
+        let mut names: Vec<String> = Vec::new();
+        loop {
+            let length = c3d[position] as usize;
+            let name = String::from_utf8(c3d[position + 2..position + 2 + length].to_vec()).unwrap();
+            let offset = position + 2 + length;
+            let next = i16::from_le_bytes([c3d[offset], c3d[offset + 1]]) as usize;
+            if name == "DATA_START" {
+                assert_eq!(u16::from_le_bytes([c3d[offset + 4], c3d[offset + 5]]), word(8));
+            }
+            names.push(name);
+            if next == 0 {
+                break;
+            }
+            position = offset + next;
+        }
+        assert_eq!(names[..3], ["POINT", "ANALOG", "TRIAL"]);
+        assert!(names.contains(&String::from("LABELS")) && names.last() == Some(&String::from("ACTUAL_END_FIELD")));
+
+        // x forward, y left and z up in millimeters, residual from visibility, missing frames are invalid
+        // and fully visible landmarks keep the minimum residual of measured points
+        let frame_size = (33 * 4 + 25) * 4;
+        assert_eq!([float(data_start), float(data_start + 4), float(data_start + 8), float(data_start + 12)], [-200.0, 100.0, 500.0, 75.0]);
+        assert_eq!(float(data_start + frame_size + 12), -1.0);
+        assert_eq!(float(data_start + 2 * frame_size + 12), 1.0);
+        assert!(super::write(&poses[..2], &visibility, &animation).is_err());
+    }
+}
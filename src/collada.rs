@@ -0,0 +1,111 @@
+extern crate cgt_math;
+use cgt_math::{Quaternion, Vector3};
+use crate::skeleton::{self, Animation};
+use std::fmt::Write;
+
+/// Writes the animation as Collada 1.4.1 document.
+/// Bones become nested joint nodes with their rest offsets, the animation is baked to a local matrix per bone and frame.
+pub fn write(animation: &Animation, scale: f32) -> String {
+    let bones = &animation.skeleton.bones;
+    let mut text = String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+    text.push_str("<COLLADA xmlns=\"http://www.collada.org/2005/11/COLLADASchema\" version=\"1.4.1\">\n");
+    text.push_str("  <asset>\n    <contributor><authoring_tool>mediapipe_rotations</authoring_tool></contributor>\n");
+    text.push_str("    <unit name=\"meter\" meter=\"1\"/>\n    <up_axis>Y_UP</up_axis>\n  </asset>\n");
+
+    // one animation per bone with shared keyframe times
+    let times: Vec<String> = (0..animation.frame_count()).map(|i| (i as f32 * animation.frame_time).to_string()).collect();
+    text.push_str("  <library_animations>\n");
+    for (idx, bone) in bones.iter().enumerate() {
+        let id = format!("{}-anim", bone.name);
+        let mut matrices: Vec<String> = Vec::with_capacity(animation.frame_count());
+        for (frame, rotations) in animation.rotations.iter().enumerate() {
+            let translation = if bone.parent.is_none() { animation.root_positions[frame] } else { bone.offset };
+            matrices.push(matrix(rotations[idx], translation, scale));
+        }
+
+        writeln!(text, "    <animation id=\"{}\">", id).unwrap();
+        write_source(&mut text, &format!("{}-input", id), &times.join(" "), times.len(), "TIME", "float", 1);
+        write_source(&mut text, &format!("{}-output", id), &matrices.join(" "), times.len(), "TRANSFORM", "float4x4", 16);
+        writeln!(text, "      <source id=\"{}-interpolation\">", id).unwrap();
+        writeln!(text, "        <Name_array id=\"{}-interpolation-array\" count=\"{}\">{}</Name_array>",
+            id, times.len(), vec!["LINEAR"; times.len()].join(" ")).unwrap();
+        writeln!(text, "        <technique_common><accessor source=\"#{}-interpolation-array\" count=\"{}\" stride=\"1\"><param name=\"INTERPOLATION\" type=\"name\"/></accessor></technique_common>",
+            id, times.len()).unwrap();
+        text.push_str("      </source>\n");
+        writeln!(text, "      <sampler id=\"{}-sampler\">", id).unwrap();
+        for semantic in ["INPUT", "OUTPUT", "INTERPOLATION"] {
+            writeln!(text, "        <input semantic=\"{}\" source=\"#{}-{}\"/>", semantic, id, semantic.to_lowercase()).unwrap();
+        }
+        text.push_str("      </sampler>\n");
+        writeln!(text, "      <channel source=\"#{}-sampler\" target=\"{}/transform\"/>", id, bone.name).unwrap();
+        text.push_str("    </animation>\n");
+    }
+    text.push_str("  </library_animations>\n");
+
+    text.push_str("  <library_visual_scenes>\n    <visual_scene id=\"scene\" name=\"scene\">\n");
+    for (idx, bone) in bones.iter().enumerate() {
+        if bone.parent.is_none() {
+            write_node(&mut text, animation, idx, 3, scale);
+        }
+    }
+    text.push_str("    </visual_scene>\n  </library_visual_scenes>\n");
+    text.push_str("  <scene>\n    <instance_visual_scene url=\"#scene\"/>\n  </scene>\n</COLLADA>\n");
+    return text;
+}
+
+fn write_node(text: &mut String, animation: &Animation, idx: usize, depth: usize, scale: f32) {
+    let bone = &animation.skeleton.bones[idx];
+    let indent = "  ".repeat(depth);
+    writeln!(text, "{}<node id=\"{}\" name=\"{}\" sid=\"{}\" type=\"JOINT\">", indent, bone.name, bone.name, bone.name).unwrap();
+    writeln!(text, "{}  <matrix sid=\"transform\">{}</matrix>", indent, matrix(Quaternion::IDENTITY, bone.offset, scale)).unwrap();
+    for child in animation.skeleton.children(idx) {
+        write_node(text, animation, child, depth + 1, scale);
+    }
+    writeln!(text, "{}</node>", indent).unwrap();
+}
+
+fn write_source(text: &mut String, id: &str, values: &str, count: usize, name: &str, kind: &str, stride: usize) {
+    writeln!(text, "      <source id=\"{}\">", id).unwrap();
+    writeln!(text, "        <float_array id=\"{}-array\" count=\"{}\">{}</float_array>", id, count * stride, values).unwrap();
+    writeln!(text, "        <technique_common><accessor source=\"#{}-array\" count=\"{}\" stride=\"{}\"><param name=\"{}\" type=\"{}\"/></accessor></technique_common>",
+        id, count, stride, name, kind).unwrap();
+    text.push_str("      </source>\n");
+}
+
+/// Row major transformation matrix of the rotation and translation.
+fn matrix(rotation: Quaternion, translation: Vector3, scale: f32) -> String {
+    // the columns of the rotation matrix are the rotated axes
+    let [x, y, z] = [Vector3::X, Vector3::Y, Vector3::Z].map(|axis| skeleton::rotate(rotation, axis));
+    let rows = [[x.x, y.x, z.x, translation.x * scale], [x.y, y.y, z.y, translation.y * scale], [x.z, y.z, z.z, translation.z * scale]];
+    let mut values: Vec<String> = Vec::with_capacity(16);
+    for row in rows.iter() {
+        for value in row.iter() {
+            values.push(value.to_string());
+        }
+    }
+    values.extend(["0", "0", "0", "1"].map(String::from));
+    return values.join(" ");
+}
+
+#[cfg(test)]
+mod test {
+    use crate::skeleton;
+    #[test]
+    fn impl_test() {
+        let frames = vec![skeleton::Frame::default(); 2];
+        let animation = skeleton::animate(&frames, 30.0, false);
+        let text = super::write(&animation, 1.0);
+
+        assert!(text.contains("<up_axis>Y_UP</up_axis>"));
+        assert_eq!(text.matches("type=\"JOINT\"").count(), 14);
+        assert_eq!(text.matches("<animation ").count(), 14);
+        assert!(text.contains("target=\"forearm.L/transform\""));
+        assert!(text.contains("<float_array id=\"hips-anim-output-array\" count=\"32\">"));
+        // joints are nested below their parents
+        let chest = text.find("<node id=\"chest\"").unwrap();
+        let arm = text.find("<node id=\"upper_arm.L\"").unwrap();
+        let thigh = text.find("<node id=\"thigh.L\"").unwrap();
+        assert!(chest < arm && arm < thigh);
+        assert_eq!(text.matches("<node ").count(), text.matches("</node>").count());
+    }
+}
@@ -0,0 +1,176 @@
+extern crate cgt_math;
+use cgt_math::Quaternion;
+use crate::skeleton::{self, RotationOrder};
+use std::fmt::Write;
+
+const LANDMARK_COLUMNS: [&str; 6] = ["frame", "landmark", "x", "y", "z", "visibility"];
+// Largest landmark count of the refined face mesh and the largest allowed gap between frame indices.
+const MAX_LANDMARKS: usize = 478;
+const MAX_FRAME_GAP: usize = 100_000;
+
+/// Landmark sequence with one entry per frame, frames without rows are empty.
+#[derive(Clone, Debug, Default)]
+pub struct Landmarks {
+    pub landmarks: Vec<Vec<[f32; 3]>>,
+    pub visibility: Vec<Vec<f32>>,
+}
+
+/// Reads landmark rows of `frame, landmark, x, y, z, visibility`.
+/// The header is optional, with a header the columns may be in any order and the visibility may be missing.
+/// The landmark count is the highest landmark index + 1, missing landmarks of a frame are NaN.
+/// Frame indices may not jump further than MAX_FRAME_GAP frames ahead of the previous highest frame.
+pub fn read_landmarks(text: &str) -> Result<Landmarks, String> {
+    let mut lines = text.lines().enumerate().filter(|(_, x)| !x.trim().is_empty()).peekable();
+    let mut columns: [Option<usize>; 6] = [Some(0), Some(1), Some(2), Some(3), Some(4), Some(5)];
+    if let Some((_, header)) = lines.peek() {
+        let fields: Vec<String> = header.split(',').map(|x| x.trim().to_lowercase()).collect();
+        if fields[0].parse::<f32>().is_err() {
+            for (i, name) in LANDMARK_COLUMNS.iter().enumerate() {
+                columns[i] = fields.iter().position(|x| x == name);
+            }
+            if columns[..5].iter().any(|x| x.is_none()) {
+                return Err(String::from("Expected the columns frame, landmark, x, y and z."));
+            }
+            lines.next();
+        }
+    }
+
+    // rows as (frame, landmark, position, visibility)
+    let mut rows: Vec<(usize, usize, [f32; 3], f32)> = Vec::new();
+    let mut last_frame = 0;
+    for (i, line) in lines {
+        let fields: Vec<&str> = line.split(',').map(|x| x.trim()).collect();
+        let field = |column: usize| -> Result<f32, String> {
+            let value = fields.get(column).ok_or(format!("Line {}: Missing column.", i + 1))?;
+            return value.parse::<f32>().map_err(|_| format!("Line {}: Invalid number {}.", i + 1, value));
+        };
+        let index = |column: usize| -> Result<usize, String> {
+            let value = fields.get(column).ok_or(format!("Line {}: Missing column.", i + 1))?;
+            return value.parse::<usize>().map_err(|_| format!("Line {}: Invalid index {}.", i + 1, value));
+        };
+        let visibility = match columns[5] {
+            Some(column) if column < fields.len() => field(column)?,
+            _ => 1.0,
+        };
+        let position = [field(columns[2].unwrap())?, field(columns[3].unwrap())?, field(columns[4].unwrap())?];
+        let (frame, landmark) = (index(columns[0].unwrap())?, index(columns[1].unwrap())?);
+        if frame > last_frame + MAX_FRAME_GAP {
+            return Err(format!("Line {}: Frame {} is more than {} frames after frame {}.", i + 1, frame, MAX_FRAME_GAP, last_frame));
+        }
+        if landmark >= MAX_LANDMARKS {
+            return Err(format!("Line {}: Landmark {} exceeds the {} landmarks of the face mesh.", i + 1, landmark, MAX_LANDMARKS));
+        }
+        last_frame = last_frame.max(frame);
+        rows.push((frame, landmark, position, visibility));
+    }
+
+    let frame_count = rows.iter().map(|x| x.0 + 1).max().unwrap_or(0);
+    let landmark_count = rows.iter().map(|x| x.1 + 1).max().unwrap_or(0);
+    let mut result = Landmarks { landmarks: vec![Vec::new(); frame_count], visibility: vec![Vec::new(); frame_count] };
+    for (frame, landmark, position, visibility) in rows {
+        if result.landmarks[frame].is_empty() {
+            result.landmarks[frame] = vec![[f32::NAN; 3]; landmark_count];
+            result.visibility[frame] = vec![0.0; landmark_count];
+        }
+        result.landmarks[frame][landmark] = position;
+        result.visibility[frame][landmark] = visibility;
+    }
+    return Ok(result);
+}
+
+/// Writes landmark rows of `frame, landmark, x, y, z, visibility`.
+pub fn write_landmarks(landmarks: &Landmarks) -> String {
+    let mut text = format!("{}\n", LANDMARK_COLUMNS.join(","));
+    for (frame, data) in landmarks.landmarks.iter().enumerate() {
+        for (i, position) in data.iter().enumerate() {
+            let visibility = landmarks.visibility.get(frame).and_then(|x| x.get(i)).copied().unwrap_or(1.0);
+            writeln!(text, "{},{},{},{},{},{}", frame, i, position[0], position[1], position[2], visibility).unwrap();
+        }
+    }
+    return text;
+}
+
+/// Names the solver rotations, NaN rotations of unsolved indices are skipped.
+pub fn named_rotations(names: &[(usize, &str)], rotations: &[Quaternion], suffix: &str) -> Vec<(String, Quaternion)> {
+    let mut result: Vec<(String, Quaternion)> = Vec::new();
+    for (idx, name) in names.iter() {
+        match rotations.get(*idx) {
+            Some(q) if q.is_finite() => result.push((format!("{}{}", name, suffix), *q)),
+            _ => {},
+        }
+    }
+    return result;
+}
+
+/// Names of the pose solver rotations following the skeleton bones.
+pub fn pose_names() -> Vec<(usize, &'static str)> {
+    return skeleton::POSE_BONES.iter().map(|x| (x.2, x.0)).collect();
+}
+
+/// Writes rotation rows of `frame, bone, w, x, y, z`, or `frame, bone, x, y, z` in radians if a rotation order is passed.
+pub fn write_rotations(frames: &[Vec<(String, Quaternion)>], order: Option<RotationOrder>) -> String {
+    let mut text = match order {
+        Some(_) => String::from("frame,bone,x,y,z\n"),
+        None => String::from("frame,bone,w,x,y,z\n"),
+    };
+    for (frame, rotations) in frames.iter().enumerate() {
+        for (bone, q) in rotations.iter() {
+            match order {
+                Some(order) => {
+                    let [x, y, z] = skeleton::to_euler(skeleton::normalize(*q), order);
+                    writeln!(text, "{},{},{},{},{}", frame, bone, x, y, z).unwrap();
+                },
+                None => writeln!(text, "{},{},{},{},{},{}", frame, bone, q.w, q.x, q.y, q.z).unwrap(),
+            }
+        }
+    }
+    return text;
+}
+
+#[cfg(test)]
+mod test {
+    use cgt_math::Quaternion;
+    use crate::skeleton::{self, RotationOrder};
+    #[test]
+    fn impl_test() {
+        // header in a different order, frame 1 is missing
+        let text = "landmark,frame,x,y,z\n0,0,0.1,0.2,0.3\n1,0,0.4,0.5,0.6\n0,2,1,2,3\n";
+        let landmarks = super::read_landmarks(text).unwrap();
+        assert_eq!(landmarks.landmarks.len(), 3);
+        assert_eq!(landmarks.landmarks[0], vec![[0.1, 0.2, 0.3], [0.4, 0.5, 0.6]]);
+        assert!(landmarks.landmarks[1].is_empty());
+        assert!(landmarks.landmarks[2][1][0].is_nan());
+        assert_eq!(landmarks.visibility[0], vec![1.0, 1.0]);
+
+        // round trip without header
+        let written = super::write_landmarks(&landmarks);
+        let body: Vec<&str> = written.lines().skip(1).collect();
+        let restored = super::read_landmarks(&body.join("\n")).unwrap();
+        assert_eq!(restored.landmarks[0], landmarks.landmarks[0]);
+        assert!(super::read_landmarks("frame,x,y,z\n").is_err());
+        assert!(super::read_landmarks("0,0,a,0,0\n").is_err());
+
+        // indices are checked before allocating the frames
+        assert!(super::read_landmarks("-1,0,0,0,0\n").is_err());
+        assert!(super::read_landmarks("1.5,0,0,0,0\n").is_err());
+        assert!(super::read_landmarks("0,0,0,0,0\n4000000000,0,0,0,0\n").is_err());
+        assert!(super::read_landmarks("0,100000,0,0,0\n").is_err());
+        assert_eq!(super::read_landmarks("0,0,0,0,0\n100000,0,0,0,0\n").unwrap().landmarks.len(), 100_001);
+
+        let rotations = vec![Quaternion::IDENTITY, Quaternion::NAN, Quaternion::IDENTITY];
+        let names = super::named_rotations(&skeleton::FACE_BONES, &rotations, "");
+        assert_eq!(names.iter().map(|x| x.0.as_str()).collect::<Vec<&str>>(), vec!["head", "mouth_corner.L"]);
+        let hand = super::named_rotations(&skeleton::HAND_BONES, &[Quaternion::IDENTITY; 21], ".R");
+        assert_eq!(hand.len(), 16);
+        assert_eq!(hand[1].0, "thumb_01.R");
+
+        let text = super::write_rotations(&[names.clone(), names], None);
+        assert_eq!(text.lines().nth(1), Some("0,head,1,0,0,0"));
+        assert_eq!(text.lines().count(), 5);
+        let text = super::write_rotations(&[hand], Some(RotationOrder::XYZ));
+        let row: Vec<&str> = text.lines().nth(1).unwrap().split(',').collect();
+        assert_eq!(row[..2], ["0", "hand.R"]);
+        assert!(row[2..].iter().all(|x| x.parse::<f32>().unwrap() == 0.0));
+        assert!(super::pose_names().contains(&(11, "upper_arm.L")));
+    }
+}
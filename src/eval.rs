@@ -0,0 +1,217 @@
+extern crate cgt_math;
+use cgt_math::{Quaternion, Vector3};
+use crate::fit;
+use crate::skeleton::{self, Animation, Skeleton, WorldTransforms, POSE_BONES};
+use std::fmt::Write;
+
+/// Common names of the pose bones in motion capture skeletons.
+pub const BONE_NAMES: [(&str, &[&str]); 14] = [
+    ("hips", &["Hips", "hips", "Pelvis", "pelvis", "Root"]),
+    ("chest", &["Chest", "chest", "Spine2", "Spine1", "Spine", "spine"]),
+    ("upper_arm.L", &["LeftArm", "LeftUpperArm", "lShldr", "upper_arm.L", "mixamorig:LeftArm"]),
+    ("forearm.L", &["LeftForeArm", "LeftLowerArm", "lForeArm", "forearm.L", "mixamorig:LeftForeArm"]),
+    ("hand.L", &["LeftHand", "lHand", "hand.L", "mixamorig:LeftHand"]),
+    ("upper_arm.R", &["RightArm", "RightUpperArm", "rShldr", "upper_arm.R", "mixamorig:RightArm"]),
+    ("forearm.R", &["RightForeArm", "RightLowerArm", "rForeArm", "forearm.R", "mixamorig:RightForeArm"]),
+    ("hand.R", &["RightHand", "rHand", "hand.R", "mixamorig:RightHand"]),
+    ("thigh.L", &["LeftUpLeg", "LeftUpperLeg", "lThigh", "thigh.L", "mixamorig:LeftUpLeg"]),
+    ("shin.L", &["LeftLeg", "LeftLowerLeg", "lShin", "shin.L", "mixamorig:LeftLeg"]),
+    ("foot.L", &["LeftFoot", "lFoot", "foot.L", "mixamorig:LeftFoot"]),
+    ("thigh.R", &["RightUpLeg", "RightUpperLeg", "rThigh", "thigh.R", "mixamorig:RightUpLeg"]),
+    ("shin.R", &["RightLeg", "RightLowerLeg", "rShin", "shin.R", "mixamorig:RightLeg"]),
+    ("foot.R", &["RightFoot", "rFoot", "foot.R", "mixamorig:RightFoot"]),
+];
+
+/// Errors of a single bone in degrees.
+#[derive(Clone, Debug)]
+pub struct BoneError {
+    pub name: String,
+    /// Mean angle between the bone directions, NaN for bones without mapped child.
+    pub direction_error: f32,
+    /// Mean orientation angle after removing the constant rest pose difference.
+    pub rotation_error: f32,
+    pub max_rotation_error: f32,
+}
+
+#[derive(Clone, Debug)]
+pub struct Report {
+    pub frames: usize,
+    /// Scale of the solved skeleton to the reference units.
+    pub scale: f32,
+    /// Mean per joint position error in reference units, relative to the hips.
+    pub mpjpe: f32,
+    pub bones: Vec<BoneError>,
+}
+
+impl Report {
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        writeln!(text, "Frames: {}", self.frames).unwrap();
+        writeln!(text, "Scale: {:.4}", self.scale).unwrap();
+        writeln!(text, "MPJPE: {:.4}", self.mpjpe).unwrap();
+        writeln!(text, "{:<14}{:>12}{:>12}{:>12}", "bone", "direction", "rotation", "max").unwrap();
+        for bone in self.bones.iter() {
+            writeln!(text, "{:<14}{:>12.2}{:>12.2}{:>12.2}",
+                bone.name, bone.direction_error, bone.rotation_error, bone.max_rotation_error).unwrap();
+        }
+        return text;
+    }
+}
+
+/// Maps the pose bones to the reference skeleton using the common names in BONE_NAMES.
+pub fn default_bone_map(reference: &Skeleton) -> Vec<(String, String)> {
+    let mut bone_map: Vec<(String, String)> = Vec::new();
+    for (bone, names) in BONE_NAMES.iter() {
+        if let Some(name) = names.iter().find(|x| reference.index(x).is_some()) {
+            bone_map.push((bone.to_string(), name.to_string()));
+        }
+    }
+    return bone_map;
+}
+
+/// Compares the solved animation with a reference recording.
+/// Reference frames are picked by time, the solved frame i is compared at i * frame_time + time_offset.
+/// The solved skeleton is aligned to the reference by a similarity fit of the hip relative joint positions over all frames.
+pub fn evaluate(solved: &Animation, reference: &Animation, bone_map: &[(String, String)], time_offset: f32) -> Result<Report, String> {
+    // pairs of bone indices in the solved and reference skeleton
+    let mut pairs: Vec<(usize, usize)> = Vec::new();
+    for (bone, reference_bone) in bone_map.iter() {
+        let a = solved.skeleton.index(bone).ok_or(format!("Missing bone {} in the solved skeleton.", bone))?;
+        let b = reference.skeleton.index(reference_bone).ok_or(format!("Missing bone {} in the reference skeleton.", reference_bone))?;
+        pairs.push((a, b));
+    }
+    let root = pairs.iter().position(|(a, _)| solved.skeleton.bones[*a].parent.is_none())
+        .ok_or("The root bone has to be mapped.")?;
+
+    // synchronised world transforms
+    let mut frames: Vec<[WorldTransforms; 2]> = Vec::new();
+    for i in 0..solved.frame_count() {
+        let time = i as f32 * solved.frame_time + time_offset;
+        let j = (time / reference.frame_time).round();
+        if j < 0.0 || j as usize >= reference.frame_count() {
+            continue;
+        }
+        frames.push([solved.world_transforms(i), reference.world_transforms(j as usize)]);
+    }
+    if frames.is_empty() {
+        return Err(String::from("The animations do not overlap."));
+    }
+
+    // joints placed at pose landmarks, the hips and chest are placed differently in most rigs
+    let joints: Vec<usize> = (0..pairs.len())
+        .filter(|x| POSE_BONES.iter().any(|b| b.0 == solved.skeleton.bones[pairs[*x].0].name && b.3[0] < 33))
+        .collect();
+    let mut source: Vec<Vector3> = Vec::new();
+    let mut target: Vec<Vector3> = Vec::new();
+    for [(solved_positions, _), (reference_positions, _)] in frames.iter() {
+        for idx in joints.iter() {
+            let (a, b) = pairs[*idx];
+            source.push(solved_positions[a] - solved_positions[pairs[root].0]);
+            target.push(reference_positions[b] - reference_positions[pairs[root].1]);
+        }
+    }
+    let transform = fit::similarity_fit(&source, &target).ok_or("Not enough joints to align the skeletons.")?;
+    let alignment = skeleton::normalize(transform.rotation);
+
+    let mut position_error = 0.0f32;
+    for (s, t) in source.iter().zip(target.iter()) {
+        let aligned = skeleton::rotate(alignment, *s) * transform.scale;
+        position_error += aligned.distance_to(*t);
+    }
+
+    let mut bones: Vec<BoneError> = Vec::new();
+    for (a, b) in pairs.iter() {
+        // bone direction towards a mapped child
+        let child = pairs.iter().find(|(x, _)| solved.skeleton.bones[*x].parent == Some(*a));
+        let mut direction_error = 0.0f32;
+        // aligned solved rotations relative to the reference rotation
+        let mut differences: Vec<Quaternion> = Vec::new();
+        for [(solved_positions, solved_rotations), (reference_positions, reference_rotations)] in frames.iter() {
+            if let Some((c, d)) = child {
+                let solved_dir = skeleton::rotate(alignment, solved_positions[*c] - solved_positions[*a]);
+                let reference_dir = reference_positions[*d] - reference_positions[*b];
+                direction_error += solved_dir.angle(reference_dir);
+            }
+            let aligned = alignment * solved_rotations[*a];
+            differences.push(skeleton::conjugate(aligned) * reference_rotations[*b]);
+        }
+        let offset = mean_rotation(&differences);
+        let errors: Vec<f32> = differences.iter().map(|x| rotation_angle(skeleton::conjugate(offset) * *x)).collect();
+
+        let count = frames.len() as f32;
+        bones.push(BoneError {
+            name: solved.skeleton.bones[*a].name.clone(),
+            direction_error: if child.is_some() { (direction_error / count).to_degrees() } else { f32::NAN },
+            rotation_error: (errors.iter().sum::<f32>() / count).to_degrees(),
+            max_rotation_error: errors.iter().fold(0.0f32, |acc, x| acc.max(*x)).to_degrees(),
+        });
+    }
+
+    return Ok(Report {
+        frames: frames.len(),
+        scale: transform.scale,
+        mpjpe: position_error / source.len() as f32,
+        bones,
+    });
+}
+
+/// Normalized sum of the quaternions in the hemisphere of the first one.
+fn mean_rotation(rotations: &[Quaternion]) -> Quaternion {
+    let first = rotations[0].to_array();
+    let mut sum = [0.0f32; 4];
+    for q in rotations.iter() {
+        let q = q.to_array();
+        let dot: f32 = (0..4).map(|i| q[i] * first[i]).sum();
+        let sign = if dot < 0.0 { -1.0 } else { 1.0 };
+        for i in 0..4 {
+            sum[i] += q[i] * sign;
+        }
+    }
+    return skeleton::normalize(skeleton::from_array(sum));
+}
+
+fn rotation_angle(q: Quaternion) -> f32 {
+    return 2.0 * q.w.abs().min(1.0).acos();
+}
+
+#[cfg(test)]
+mod test {
+    use cgt_math::Quaternion;
+    use crate::skeleton;
+    #[test]
+    fn impl_test() {
+        // a rotated and scaled copy of the solved animation matches without error
+        let frames = vec![skeleton::Frame::default(); 4];
+        let mut solved = skeleton::animate(&frames, 30.0, false);
+        for (i, frame) in solved.rotations.iter_mut().enumerate() {
+            frame[3] = Quaternion::from_rotation_z(i as f32 * 0.2);
+            frame[9] = Quaternion::from_rotation_x(i as f32 * 0.1);
+        }
+        let mut reference = solved.clone();
+        let turn = Quaternion::from_rotation_y(1.0);
+        for bone in reference.skeleton.bones.iter_mut() {
+            bone.offset = bone.offset * 100.0;
+            bone.name = bone.name.replace("upper_arm.L", "LeftArm");
+        }
+        for frame in reference.rotations.iter_mut() {
+            frame[0] = turn * frame[0];
+        }
+
+        let bone_map = super::default_bone_map(&reference.skeleton);
+        assert_eq!(bone_map.len(), 14);
+        assert!(bone_map.contains(&(String::from("upper_arm.L"), String::from("LeftArm"))));
+
+        let report = super::evaluate(&solved, &reference, &bone_map, 0.0).unwrap();
+        assert_eq!(report.frames, 4);
+        assert!((report.scale - 100.0).abs() < 1e-2);
+        assert!(report.mpjpe < 1e-2);
+        for bone in report.bones.iter() {
+            assert!(bone.rotation_error < 0.5);
+            assert!(bone.direction_error.is_nan() || bone.direction_error < 0.5);
+        }
+        assert!(report.to_text().contains("MPJPE"));
+
+        // shifted beyond the reference
+        assert!(super::evaluate(&solved, &reference, &bone_map, 10.0).is_err());
+    }
+}
@@ -0,0 +1,190 @@
+extern crate cgt_math;
+use cgt_math::{Quaternion, Vector3};
+use crate::skeleton::rotate;
+
+/// Similarity transform mapping source points onto target points.
+/// target = scale * rotation * source + translation
+#[derive(Clone, Copy, Debug)]
+pub struct SimilarityTransform {
+    pub rotation: Quaternion,
+    pub translation: Vector3,
+    pub scale: f32,
+    /// Root mean square distance of the transformed source to the target points.
+    pub error: f32,
+}
+
+/// Least squares fit of rotation, translation and scale based on Umeyama.
+/// The rotation is obtained from the covariance matrix using Horns quaternion method.
+/// Points containing NaNs are skipped, returns None if less than three valid pairs remain.
+pub fn similarity_fit(source: &[Vector3], target: &[Vector3]) -> Option<SimilarityTransform> {
+    let mut pairs: Vec<(Vector3, Vector3)> = Vec::new();
+    for (s, t) in source.iter().zip(target.iter()) {
+        if [s.x, s.y, s.z, t.x, t.y, t.z].iter().all(|x| x.is_finite()) {
+            pairs.push((*s, *t));
+        }
+    }
+    if pairs.len() < 3 {
+        return None;
+    }
+
+    // centroids
+    let n = pairs.len() as f32;
+    let mut source_center = Vector3::ZERO;
+    let mut target_center = Vector3::ZERO;
+    for (s, t) in pairs.iter() {
+        source_center += *s / n;
+        target_center += *t / n;
+    }
+
+    // covariance of the centered points, row i holds s[i] * t
+    let mut cov = [Vector3::ZERO; 3];
+    let mut source_var = 0.0f32;
+    for (s, t) in pairs.iter() {
+        let s = *s - source_center;
+        let t = *t - target_center;
+        cov[0] += t * s.x;
+        cov[1] += t * s.y;
+        cov[2] += t * s.z;
+        source_var += s.dot(s);
+    }
+    if source_var <= f32::EPSILON {
+        return None;
+    }
+
+    // Horns symmetric matrix, its dominant eigenvector is the rotation quaternion (w, x, y, z)
+    let [(sxx, sxy, sxz), (syx, syy, syz), (szx, szy, szz)] = cov.map(|x| (x.x, x.y, x.z));
+    let horn = [
+        [sxx + syy + szz, syz - szy, szx - sxz, sxy - syx],
+        [syz - szy, sxx - syy - szz, sxy + syx, szx + sxz],
+        [szx - sxz, sxy + syx, -sxx + syy - szz, syz + szy],
+        [sxy - syx, szx + sxz, syz + szy, -sxx - syy + szz],
+    ];
+    let [w, x, y, z] = dominant_eigenvector(horn);
+    let rotation = Quaternion { x, y, z, w };
+
+    // scale and translation
+    let mut projected = 0.0f32;
+    for (s, t) in pairs.iter() {
+        projected += rotate(rotation, *s - source_center).dot(*t - target_center);
+    }
+    let scale = projected / source_var;
+    let translation = target_center - rotate(rotation, source_center) * scale;
+
+    // residual
+    let mut squared_error = 0.0f32;
+    for (s, t) in pairs.iter() {
+        let diff = rotate(rotation, *s) * scale + translation - *t;
+        squared_error += diff.dot(diff);
+    }
+
+    return Some(SimilarityTransform {
+        rotation,
+        translation,
+        scale,
+        error: (squared_error / n).sqrt(),
+    });
+}
+
+/// Jacobi eigenvalue iteration of a symmetric 4x4 matrix, returns the eigenvector of the largest eigenvalue.
+fn dominant_eigenvector(mut a: [[f32; 4]; 4]) -> [f32; 4] {
+    let mut v = [[0.0f32; 4]; 4];
+    for i in 0..4 {
+        v[i][i] = 1.0;
+    }
+
+    for _ in 0..32 {
+        let mut off_diagonal = 0.0f32;
+        for p in 0..4 {
+            for q in (p + 1)..4 {
+                off_diagonal += a[p][q] * a[p][q];
+            }
+        }
+        if off_diagonal < 1e-12 {
+            break;
+        }
+
+        for p in 0..4 {
+            for q in (p + 1)..4 {
+                if a[p][q].abs() < 1e-12 {
+                    continue;
+                }
+                // rotation angle which zeroes a[p][q]
+                let theta = (a[q][q] - a[p][p]) / (2.0 * a[p][q]);
+                let t = theta.signum() / (theta.abs() + (theta * theta + 1.0).sqrt());
+                let c = 1.0 / (t * t + 1.0).sqrt();
+                let s = t * c;
+                for k in 0..4 {
+                    let (akp, akq) = (a[k][p], a[k][q]);
+                    a[k][p] = c * akp - s * akq;
+                    a[k][q] = s * akp + c * akq;
+                }
+                for k in 0..4 {
+                    let (apk, aqk) = (a[p][k], a[q][k]);
+                    a[p][k] = c * apk - s * aqk;
+                    a[q][k] = s * apk + c * aqk;
+                }
+                for k in 0..4 {
+                    let (vkp, vkq) = (v[k][p], v[k][q]);
+                    v[k][p] = c * vkp - s * vkq;
+                    v[k][q] = s * vkp + c * vkq;
+                }
+            }
+        }
+    }
+
+    let mut largest = 0;
+    for i in 1..4 {
+        if a[i][i] > a[largest][largest] {
+            largest = i;
+        }
+    }
+    return [v[0][largest], v[1][largest], v[2][largest], v[3][largest]];
+}
+
+#[cfg(test)]
+mod test {
+    use cgt_math::{Quaternion, Vector3};
+    use crate::skeleton;
+    #[test]
+    fn impl_test() {
+        let source = [
+            Vector3::from_array([0.0, 0.0, 0.0]),
+            Vector3::from_array([1.0, 0.0, 0.0]),
+            Vector3::from_array([0.0, 2.0, 0.0]),
+            Vector3::from_array([0.0, 0.0, 3.0]),
+            Vector3::from_array([1.0, 1.0, 1.0]),
+        ];
+        // rotate 90 degrees around z, scale by 2 and translate
+        let target: Vec<Vector3> = source.iter()
+            .map(|v| Vector3::from_array([-v.y * 2.0 + 1.0, v.x * 2.0 + 2.0, v.z * 2.0 + 3.0]))
+            .collect();
+
+        let fit = super::similarity_fit(&source, &target).unwrap();
+        assert!((fit.scale - 2.0).abs() < 1e-4);
+        assert!(fit.error < 1e-4);
+        assert!((fit.translation.x - 1.0).abs() < 1e-4);
+        assert!((fit.translation.y - 2.0).abs() < 1e-4);
+        assert!((fit.translation.z - 3.0).abs() < 1e-4);
+        let s = std::f32::consts::FRAC_1_SQRT_2;
+        assert!(same_rotation(fit.rotation.to_array(), [0.0, 0.0, s, s]));
+
+        // arbitrary rotation and translation without scale
+        let q = skeleton::normalize(Quaternion { x: 0.3, y: -0.5, z: 0.2, w: 0.8 });
+        let target: Vec<Vector3> = source.iter().map(|v| skeleton::rotate(q, *v) + Vector3::new(-0.5, 0.25, 4.0)).collect();
+        let fit = super::similarity_fit(&source, &target).unwrap();
+        assert!(same_rotation(fit.rotation.to_array(), q.to_array()));
+        assert!((fit.scale - 1.0).abs() < 1e-4);
+        assert!((fit.translation.x + 0.5).abs() < 1e-4);
+        assert!((fit.translation.y - 0.25).abs() < 1e-4);
+        assert!((fit.translation.z - 4.0).abs() < 1e-4);
+
+        // degenerate input
+        assert!(super::similarity_fit(&source[..2], &target[..2]).is_none());
+    }
+
+    /// Quaternions q and -q describe the same rotation.
+    fn same_rotation(a: [f32; 4], b: [f32; 4]) -> bool {
+        let dot: f32 = a.iter().zip(b.iter()).map(|(a, b)| a * b).sum();
+        return dot.abs() > 1.0 - 1e-5;
+    }
+}
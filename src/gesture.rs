@@ -0,0 +1,167 @@
+extern crate cgt_math;
+use cgt_math::Vector3;
+use crate::hand::{self, normalize};
+
+pub const GESTURES: [&str; 7] = ["fist", "open_palm", "point", "pinch", "thumbs_up", "peace", "ok"];
+
+// Thumb to index tip distance relative to the palm length.
+const PINCH_CLOSED: f32 = 0.2;
+const PINCH_OPEN: f32 = 0.6;
+
+/// Calculates a 0..1 score for every gesture in GESTURES.
+pub fn scores(hand: &[[f32; 3]]) -> [f32; 7] {
+    let angles = hand::angles(hand);
+    let mut data = hand::to_vectors(hand);
+    hand::set_hand_origin(&mut data);
+
+    let curl_spread = hand::curl_spread(&angles, &hand::FINGER_RANGES);
+    let mut curl = [0.0f32; 5];
+    let mut ext = [0.0f32; 5];
+    for i in 0..5 {
+        curl[i] = curl_spread[i][0];
+        ext[i] = 1.0 - curl[i];
+    }
+
+    // thumb and index tips touching, relative to the hand size
+    let palm = data[0].distance_to(data[9]);
+    let pinch_dist = data[4].distance_to(data[8]) / palm;
+    let closeness = 1.0 - normalize(pinch_dist, PINCH_CLOSED, PINCH_OPEN);
+
+    // thumb pointing upwards, the hand origin maps the image y-axis to -z
+    let thumb_dir = (data[4] - data[2]).normalize();
+    let up = thumb_dir.dot(Vector3::Z).max(0.0);
+
+    let others_ext = ext[2].min(ext[3]).min(ext[4]);
+    let mut result = [0.0f32; 7];
+    result[0] = curl[1].min(curl[2]).min(curl[3]).min(curl[4]);
+    result[1] = ext[0].min(ext[1]).min(others_ext);
+    result[2] = ext[1].min(curl[2]).min(curl[3]).min(curl[4]);
+    result[3] = closeness * (1.0 - others_ext);
+    result[4] = ext[0].min(result[0]) * up;
+    result[5] = ext[1].min(ext[2]).min(curl[3]).min(curl[4]);
+    result[6] = closeness * others_ext;
+
+    for x in result.iter_mut() {
+        if x.is_nan() {
+            *x = 0.0;
+        }
+    }
+    return result;
+}
+
+/// Returns the index of the best matching gesture if it exceeds the threshold.
+pub fn classify(scores: &[f32; 7], threshold: f32) -> Option<usize> {
+    let mut best: Option<usize> = None;
+    for (i, score) in scores.iter().enumerate() {
+        if *score < threshold {
+            continue;
+        }
+        match best {
+            Some(b) if scores[b] >= *score => {},
+            _ => best = Some(i),
+        }
+    }
+    return best;
+}
+
+/// Debounces classified gestures over consecutive frames.
+/// A gesture only becomes active after it has been detected for `hold_frames` frames.
+pub struct GestureTracker {
+    pub threshold: f32,
+    pub hold_frames: usize,
+    candidate: Option<usize>,
+    count: usize,
+    current: Option<usize>,
+    scores: [f32; 7],
+}
+
+impl GestureTracker {
+    pub fn new(threshold: f32, hold_frames: usize) -> GestureTracker {
+        return GestureTracker {
+            threshold,
+            hold_frames,
+            candidate: None,
+            count: 0,
+            current: None,
+            scores: [0.0; 7],
+        };
+    }
+
+    /// Updates the tracker with a new frame, missing hands count as no gesture.
+    pub fn update(&mut self, hand: &[[f32; 3]]) -> Option<usize> {
+        if hand.len() == 21 {
+            self.scores = scores(hand);
+        }
+        else {
+            self.scores = [0.0; 7];
+        }
+
+        let detected = classify(&self.scores, self.threshold);
+        if detected == self.candidate {
+            self.count += 1;
+        }
+        else {
+            self.candidate = detected;
+            self.count = 1;
+        }
+
+        if self.count >= self.hold_frames {
+            self.current = self.candidate;
+        }
+        return self.current;
+    }
+
+    /// Scores of the last update.
+    pub fn scores(&self) -> [f32; 7] {
+        return self.scores;
+    }
+
+    pub fn reset(&mut self) {
+        self.candidate = None;
+        self.count = 0;
+        self.current = None;
+        self.scores = [0.0; 7];
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::hand::test::synthetic_hand;
+
+    #[test]
+    fn impl_test() {
+        // classification picks the highest score above threshold
+        let scores = [0.1, 0.9, 0.2, 0.0, 0.0, 0.95, 0.0];
+        assert_eq!(super::classify(&scores, 0.5), Some(5));
+        assert_eq!(super::classify(&scores, 0.99), None);
+
+        let open_palm = synthetic_hand(0.0);
+        let fist = synthetic_hand(1.45);
+        let open_scores = super::scores(&open_palm);
+        let fist_scores = super::scores(&fist);
+        assert!(open_scores[1] > 0.9 && open_scores[0] < 0.1);
+        assert!(fist_scores[0] > 0.9 && fist_scores[1] < 0.1);
+        assert_eq!(super::classify(&open_scores, 0.6), Some(1));
+        assert_eq!(super::classify(&fist_scores, 0.6), Some(0));
+
+        // gestures switch only after being held for hold_frames consistent frames
+        let mut tracker = super::GestureTracker::new(0.6, 3);
+        assert_eq!(tracker.update(&open_palm), None);
+        assert_eq!(tracker.update(&open_palm), None);
+        assert_eq!(tracker.update(&open_palm), Some(1));
+        assert_eq!(tracker.update(&fist), Some(1));
+        assert_eq!(tracker.update(&fist), Some(1));
+        assert_eq!(tracker.update(&open_palm), Some(1));
+        assert_eq!(tracker.update(&fist), Some(1));
+        assert_eq!(tracker.update(&fist), Some(1));
+        assert_eq!(tracker.update(&fist), Some(0));
+        assert_eq!(tracker.scores(), fist_scores);
+
+        // missing hands release the gesture after being held as well
+        let empty: Vec<[f32; 3]> = Vec::new();
+        assert_eq!(tracker.update(&empty), Some(0));
+        assert_eq!(tracker.update(&empty), Some(0));
+        assert_eq!(tracker.update(&empty), None);
+        assert_eq!(tracker.scores(), [0.0; 7]);
+    }
+}
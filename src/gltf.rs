@@ -0,0 +1,214 @@
+extern crate cgt_math;
+use cgt_math::Vector3;
+use crate::skeleton::Animation;
+use std::fmt::Write;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+const GLB_MAGIC: u32 = 0x46546C67;
+const GLB_JSON: u32 = 0x4E4F534A;
+const GLB_BIN: u32 = 0x004E4942;
+const FLOAT: u32 = 5126;
+
+/// Writes the animation as glTF 2.0 JSON and binary buffer.
+/// Bones become skinned joint nodes with their rest offsets as translation, every bone gets a rotation channel
+/// and the root a translation channel. Pass the file name of the buffer as `bin_uri` or None to embed it in a GLB.
+/// Fails for animations without frames as glTF accessors and buffer views must not be empty.
+pub fn write(animation: &Animation, scale: f32, bin_uri: Option<&str>) -> Result<(String, Vec<u8>), String> {
+    let bones = &animation.skeleton.bones;
+    let frame_count = animation.frame_count();
+    if frame_count == 0 {
+        return Err(String::from("Cannot write a glTF animation without frames."));
+    }
+    let mut buffer: Vec<u8> = Vec::new();
+    // accessors as (byte offset, count, type, min and max)
+    let mut accessors: Vec<(usize, usize, &str, Option<String>)> = Vec::new();
+
+    // keyframe times
+    let times: Vec<f32> = (0..frame_count).map(|i| i as f32 * animation.frame_time).collect();
+    let range = format!("\"min\":[0],\"max\":[{}]", times.last().copied().unwrap_or(0.0));
+    accessors.push((push_floats(&mut buffer, &times), frame_count, "SCALAR", Some(range)));
+
+    // root translation followed by the rotation of each bone
+    let translations: Vec<f32> = animation.root_positions.iter().flat_map(|x| [x.x, x.y, x.z].map(|v| v * scale)).collect();
+    accessors.push((push_floats(&mut buffer, &translations), frame_count, "VEC3", None));
+    for i in 0..bones.len() {
+        let rotations: Vec<f32> = animation.rotations.iter().flat_map(|x| x[i].to_array()).collect();
+        accessors.push((push_floats(&mut buffer, &rotations), frame_count, "VEC4", None));
+    }
+
+    // inverse bind matrices of the rest pose, the rest pose has no rotations
+    let mut rest: Vec<Vector3> = Vec::with_capacity(bones.len());
+    for bone in bones.iter() {
+        let parent = bone.parent.map(|p| rest[p]).unwrap_or(Vector3::ZERO);
+        rest.push(parent + bone.offset);
+    }
+    let mut matrices: Vec<f32> = Vec::with_capacity(bones.len() * 16);
+    for position in rest.iter() {
+        let [x, y, z] = [position.x, position.y, position.z].map(|v| -v * scale);
+        matrices.extend_from_slice(&[1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, x, y, z, 1.0]);
+    }
+    let inverse_bind = accessors.len();
+    accessors.push((push_floats(&mut buffer, &matrices), bones.len(), "MAT4", None));
+
+    let mut json = String::from("{\"asset\":{\"version\":\"2.0\",\"generator\":\"mediapipe_rotations\"}");
+    let roots: Vec<String> = (0..bones.len()).filter(|x| bones[*x].parent.is_none()).map(|x| x.to_string()).collect();
+    write!(json, ",\"scene\":0,\"scenes\":[{{\"nodes\":[{}]}}]", roots.join(",")).unwrap();
+
+    let nodes: Vec<String> = bones.iter().enumerate().map(|(i, bone)| {
+        let children: Vec<String> = (0..bones.len()).filter(|x| bones[*x].parent == Some(i)).map(|x| x.to_string()).collect();
+        let mut node = format!("{{\"name\":\"{}\",\"translation\":[{}]", escape(&bone.name), join(&[bone.offset.x, bone.offset.y, bone.offset.z].map(|v| v * scale)));
+        if !children.is_empty() {
+            write!(node, ",\"children\":[{}]", children.join(",")).unwrap();
+        }
+        node.push('}');
+        node
+    }).collect();
+    write!(json, ",\"nodes\":[{}]", nodes.join(",")).unwrap();
+
+    let joints: Vec<String> = (0..bones.len()).map(|x| x.to_string()).collect();
+    write!(json, ",\"skins\":[{{\"name\":\"skeleton\",\"inverseBindMatrices\":{},\"skeleton\":{},\"joints\":[{}]}}]",
+        inverse_bind, roots.first().map(|x| x.as_str()).unwrap_or("0"), joints.join(",")).unwrap();
+
+    // samplers share the time accessor, the translation is sampler 0 and rotations follow
+    let mut samplers: Vec<String> = Vec::new();
+    let mut channels: Vec<String> = Vec::new();
+    if let Some(root) = roots.first() {
+        samplers.push(String::from("{\"input\":0,\"output\":1,\"interpolation\":\"LINEAR\"}"));
+        channels.push(format!("{{\"sampler\":0,\"target\":{{\"node\":{},\"path\":\"translation\"}}}}", root));
+    }
+    for i in 0..bones.len() {
+        channels.push(format!("{{\"sampler\":{},\"target\":{{\"node\":{},\"path\":\"rotation\"}}}}", samplers.len(), i));
+        samplers.push(format!("{{\"input\":0,\"output\":{},\"interpolation\":\"LINEAR\"}}", i + 2));
+    }
+    write!(json, ",\"animations\":[{{\"name\":\"mediapipe\",\"samplers\":[{}],\"channels\":[{}]}}]",
+        samplers.join(","), channels.join(",")).unwrap();
+
+    let views: Vec<String> = accessors.iter()
+        .map(|(offset, count, kind, _)| format!("{{\"buffer\":0,\"byteOffset\":{},\"byteLength\":{}}}", offset, count * 4 * components(kind)))
+        .collect();
+    write!(json, ",\"bufferViews\":[{}]", views.join(",")).unwrap();
+    let accessor_json: Vec<String> = accessors.iter().enumerate().map(|(i, (_, count, kind, range))| {
+        let mut accessor = format!("{{\"bufferView\":{},\"componentType\":{},\"count\":{},\"type\":\"{}\"", i, FLOAT, count, kind);
+        if let Some(range) = range {
+            write!(accessor, ",{}", range).unwrap();
+        }
+        accessor.push('}');
+        accessor
+    }).collect();
+    write!(json, ",\"accessors\":[{}]", accessor_json.join(",")).unwrap();
+
+    match bin_uri {
+        Some(uri) => write!(json, ",\"buffers\":[{{\"uri\":\"{}\",\"byteLength\":{}}}]}}", escape(uri), buffer.len()).unwrap(),
+        None => write!(json, ",\"buffers\":[{{\"byteLength\":{}}}]}}", buffer.len()).unwrap(),
+    }
+    return Ok((json, buffer));
+}
+
+/// Writes the animation as binary glTF.
+pub fn write_glb(animation: &Animation, scale: f32) -> Result<Vec<u8>, String> {
+    let (json, mut buffer) = write(animation, scale, None)?;
+    let mut json = json.into_bytes();
+    while json.len() % 4 != 0 {
+        json.push(b' ');
+    }
+    while buffer.len() % 4 != 0 {
+        buffer.push(0);
+    }
+
+    let length = 12 + 8 + json.len() + 8 + buffer.len();
+    let mut glb: Vec<u8> = Vec::with_capacity(length);
+    for value in [GLB_MAGIC, 2, length as u32, json.len() as u32, GLB_JSON] {
+        glb.extend_from_slice(&value.to_le_bytes());
+    }
+    glb.extend_from_slice(&json);
+    glb.extend_from_slice(&(buffer.len() as u32).to_le_bytes());
+    glb.extend_from_slice(&GLB_BIN.to_le_bytes());
+    glb.extend_from_slice(&buffer);
+    return Ok(glb);
+}
+
+/// Saves a .glb or a .gltf with the buffer in a .bin file next to it.
+/// An animation without frames is reported as InvalidInput.
+pub fn save(path: &Path, animation: &Animation, scale: f32) -> io::Result<()> {
+    let invalid = |err: String| io::Error::new(io::ErrorKind::InvalidInput, err);
+    if path.extension().is_some_and(|x| x.eq_ignore_ascii_case("glb")) {
+        return fs::write(path, write_glb(animation, scale).map_err(invalid)?);
+    }
+    let bin_path = path.with_extension("bin");
+    let bin_uri = bin_path.file_name().and_then(|x| x.to_str()).unwrap_or("animation.bin");
+    let (json, buffer) = write(animation, scale, Some(bin_uri)).map_err(invalid)?;
+    fs::write(&bin_path, buffer)?;
+    return fs::write(path, json);
+}
+
+fn push_floats(buffer: &mut Vec<u8>, values: &[f32]) -> usize {
+    let offset = buffer.len();
+    for value in values.iter() {
+        buffer.extend_from_slice(&value.to_le_bytes());
+    }
+    return offset;
+}
+
+fn components(kind: &str) -> usize {
+    match kind {
+        "VEC3" => 3,
+        "VEC4" => 4,
+        "MAT4" => 16,
+        _ => 1,
+    }
+}
+
+/// Escapes a string for a JSON string literal.
+fn escape(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            c if (c as u32) < 0x20 => write!(result, "\\u{:04x}", c as u32).unwrap(),
+            c => result.push(c),
+        }
+    }
+    return result;
+}
+
+fn join(values: &[f32]) -> String {
+    let values: Vec<String> = values.iter().map(|x| x.to_string()).collect();
+    return values.join(",");
+}
+
+#[cfg(test)]
+mod test {
+    use crate::skeleton;
+    #[test]
+    fn impl_test() {
+        let frames = vec![skeleton::Frame::default(); 3];
+        let animation = skeleton::animate(&frames, 30.0, true);
+        let bones = animation.skeleton.bones.len();
+
+        let (json, buffer) = super::write(&animation, 1.0, Some("take.bin")).unwrap();
+        assert!(json.contains("\"uri\":\"take.bin\""));
+        assert!(json.contains("\"name\":\"index_01.L\""));
+        assert_eq!(json.matches("\"path\":\"rotation\"").count(), bones);
+        assert_eq!(json.matches("\"path\":\"translation\"").count(), 1);
+        // times, root translations, rotations and inverse bind matrices
+        assert_eq!(buffer.len(), (3 + 3 * 3 + bones * 3 * 4 + bones * 16) * 4);
+
+        let glb = super::write_glb(&animation, 1.0).unwrap();
+        assert_eq!(&glb[0..4], b"glTF");
+        assert_eq!(u32::from_le_bytes([glb[8], glb[9], glb[10], glb[11]]) as usize, glb.len());
+        assert_eq!(glb.len() % 4, 0);
+
+        // names are escaped and empty animations are rejected
+        assert_eq!(super::escape("a\"b\\c\n"), "a\\\"b\\\\c\\u000a");
+        let mut renamed = animation.clone();
+        renamed.skeleton.bones[0].name = String::from("hips \"root\"");
+        let (json, _) = super::write(&renamed, 1.0, None).unwrap();
+        assert!(json.contains("\"name\":\"hips \\\"root\\\"\""));
+        let empty = skeleton::animate(&[], 30.0, false);
+        assert!(super::write(&empty, 1.0, None).is_err());
+        assert!(super::write_glb(&empty, 1.0).is_err());
+    }
+}
@@ -1,7 +1,7 @@
 extern crate cgt_math;
 use cgt_math::{Plane, Quaternion, Vector3, Points};
 
-const FINGERS: [[usize; 4]; 5] = [
+pub(crate) const FINGERS: [[usize; 4]; 5] = [
     [1, 2, 3, 4],
     [5, 6, 7, 8],
     [9, 10, 11, 12],
@@ -10,8 +10,126 @@ const FINGERS: [[usize; 4]; 5] = [
 ];
 const JOINTS: [[usize; 3]; 3] = [[0, 1, 2], [1, 2, 3], [2, 3, 4]];
 
+/// Angle range in radians used to normalize finger curl and spread.
+/// Curl is the sum of the finger's joint x-angles, spread the mcp z-angle.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FingerRange {
+    pub curl_min: f32,
+    pub curl_max: f32,
+    pub spread_min: f32,
+    pub spread_max: f32,
+}
+
+pub const FINGER_RANGES: [FingerRange; 5] = [
+    FingerRange { curl_min: 0.0, curl_max: 2.0, spread_min: -0.2, spread_max: 1.2 }, // thumb
+    FingerRange { curl_min: 0.0, curl_max: 4.0, spread_min: -0.35, spread_max: 0.35 }, // index
+    FingerRange { curl_min: 0.0, curl_max: 4.0, spread_min: -0.25, spread_max: 0.25 }, // middle
+    FingerRange { curl_min: 0.0, curl_max: 4.0, spread_min: -0.3, spread_max: 0.3 }, // ring
+    FingerRange { curl_min: 0.0, curl_max: 3.8, spread_min: -0.45, spread_max: 0.45 }, // pinky
+];
+
+impl FingerRange {
+    /// Checks that both ranges are finite and not empty or inverted, normalize divides by their width.
+    pub fn validate(&self) -> Result<(), String> {
+        let ranges = [
+            ("curl", self.curl_min, self.curl_max),
+            ("spread", self.spread_min, self.spread_max),
+        ];
+        for (name, min, max) in ranges.iter() {
+            if !min.is_finite() || !max.is_finite() {
+                return Err(format!("The {} range must be finite.", name));
+            }
+            if min >= max {
+                return Err(format!("The {} minimum {} must be below the maximum {}.", name, min, max));
+            }
+        }
+        return Ok(());
+    }
+}
+
+// Wrist to knuckle center distance relative to the knuckle width of pose landmarks.
+const POSE_FIST_RATIO: f32 = 1.0;
+const POSE_OPEN_RATIO: f32 = 2.2;
+
+/// Flexion (x-angle) and abduction (z-angle) range of a joint in radians.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct JointLimit {
+    pub flexion_min: f32,
+    pub flexion_max: f32,
+    pub abduction_min: f32,
+    pub abduction_max: f32,
+}
+
+impl JointLimit {
+    /// Checks that both ranges are finite and not inverted.
+    pub fn validate(&self) -> Result<(), String> {
+        let ranges = [
+            ("flexion", self.flexion_min, self.flexion_max),
+            ("abduction", self.abduction_min, self.abduction_max),
+        ];
+        for (name, min, max) in ranges.iter() {
+            if !min.is_finite() || !max.is_finite() {
+                return Err(format!("The {} limits must be finite.", name));
+            }
+            if min > max {
+                return Err(format!("The {} minimum {} exceeds the maximum {}.", name, min, max));
+            }
+        }
+        return Ok(());
+    }
+}
+
+/// Anatomical joint limits applied to the finger solve.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct JointLimits {
+    pub thumb_cmc: JointLimit,
+    pub thumb_mcp: JointLimit,
+    pub thumb_ip: JointLimit,
+    pub mcp: JointLimit,
+    pub pip: JointLimit,
+    pub dip: JointLimit,
+}
+
+impl Default for JointLimits {
+    fn default() -> Self {
+        JointLimits {
+            thumb_cmc: JointLimit { flexion_min: 0.0, flexion_max: 0.9, abduction_min: -0.3, abduction_max: 1.4 },
+            thumb_mcp: JointLimit { flexion_min: 0.0, flexion_max: 1.0, abduction_min: -0.3, abduction_max: 0.3 },
+            thumb_ip: JointLimit { flexion_min: 0.0, flexion_max: 1.4, abduction_min: 0.0, abduction_max: 0.0 },
+            mcp: JointLimit { flexion_min: -0.5, flexion_max: 1.6, abduction_min: -0.4, abduction_max: 0.4 },
+            pip: JointLimit { flexion_min: 0.0, flexion_max: 1.9, abduction_min: 0.0, abduction_max: 0.0 },
+            dip: JointLimit { flexion_min: 0.0, flexion_max: 1.4, abduction_min: 0.0, abduction_max: 0.0 },
+        }
+    }
+}
+
+impl JointLimits {
+    /// Limit for the landmark index, wrist and tips are not limited.
+    pub fn get(&self, idx: usize) -> Option<JointLimit> {
+        match idx {
+            1 => Some(self.thumb_cmc),
+            2 => Some(self.thumb_mcp),
+            3 => Some(self.thumb_ip),
+            5 | 9 | 13 | 17 => Some(self.mcp),
+            6 | 10 | 14 | 18 => Some(self.pip),
+            7 | 11 | 15 | 19 => Some(self.dip),
+            _ => None,
+        }
+    }
+}
+
 
 pub fn main(hand: &[[f32; 3]]) -> [Quaternion; 21] {
+    return solve(hand, None).0;
+}
+
+/// Solves the hand with joint limits applied.
+/// Additionally returns a flag for every joint which has been clamped.
+pub fn main_limited(hand: &[[f32; 3]], limits: &JointLimits) -> ([Quaternion; 21], [bool; 21]) {
+    return solve(hand, Some(limits));
+}
+
+fn solve(hand: &[[f32; 3]], limits: Option<&JointLimits>) -> ([Quaternion; 21], [bool; 21]) {
     let mut data = to_vectors(hand);
     set_hand_origin(&mut data);
 
@@ -19,15 +137,115 @@ pub fn main(hand: &[[f32; 3]]) -> [Quaternion; 21] {
     x_angles(&data, &mut angles);
     z_angles(&data, &mut angles);
 
+    let mut clamped = [false; 21];
+    if let Some(limits) = limits {
+        clamped = clamp_angles(&mut angles, limits);
+    }
+
     let mut rotation_data = [Quaternion::NAN; 21];
     angles_to_quaternions(&angles, &mut rotation_data);
+    thumb_rotation(&data, &mut rotation_data);
     hand_rotation(&data, &mut rotation_data);
 
+    return (rotation_data, clamped);
+}
+
+/// Coarse hand rotations based on pose landmarks, meant as fallback if the hand detection drops out.
+/// Uses the pose wrist, pinky and index (15, 17, 19 or 16, 18, 20) to estimate the wrist rotation
+/// and applies a rough overall curl to all finger joints. The pose thumb is a tip, not a palm point, so it isn't used.
+pub fn from_pose(pose: &[[f32; 3]], left: bool) -> [Quaternion; 21] {
+    let idx = if left { [15, 17, 19] } else { [16, 18, 20] };
+    let mut data = [Vector3::ZERO; 21];
+    data[0] = Vector3::from_array(pose[idx[0]]);
+    data[17] = Vector3::from_array(pose[idx[1]]);
+    data[5] = Vector3::from_array(pose[idx[2]]);
+    set_hand_origin(&mut data);
+
+    // the pose index and pinky move towards the wrist while making a fist
+    let knuckle_center = data[5].center(data[17]);
+    let ratio = data[0].distance_to(knuckle_center) / data[5].distance_to(data[17]);
+    let curl = 1.0 - normalize(ratio, POSE_FIST_RATIO, POSE_OPEN_RATIO);
+
+    let mut angles = [[f32::NAN; 2]; 21];
+    for (i, finger) in FINGERS.iter().enumerate() {
+        let angle = curl * FINGER_RANGES[i].curl_max / 3.0;
+        for joint in finger.iter().take(3) {
+            angles[*joint][0] = angle;
+        }
+    }
+
+    let mut rotation_data = [Quaternion::NAN; 21];
+    angles_to_quaternions(&angles, &mut rotation_data);
+    // the wrist to knuckle center direction stands in for the thumb cmc to index mcp direction
+    rotation_data[0] = wrist_rotation(knuckle_center - data[0], data[17] - data[5]);
     return rotation_data;
 }
 
+/// Calculates finger x- and z-angles without converting them to rotations.
+pub fn angles(hand: &[[f32; 3]]) -> [[f32; 2]; 21] {
+    let mut data = to_vectors(hand);
+    set_hand_origin(&mut data);
+
+    let mut angles = [[f32::NAN; 2]; 21];
+    x_angles(&data, &mut angles);
+    z_angles(&data, &mut angles);
+    return angles;
+}
+
+/// Calculates the thumb opposition twist around the metacarpal without converting it to a rotation.
+pub fn thumb_opposition(hand: &[[f32; 3]]) -> f32 {
+    let mut data = to_vectors(hand);
+    set_hand_origin(&mut data);
+    return thumb_twist(&data);
+}
+
+/// Clamps finger angles to the joint limits, returns which joints have been clamped.
+/// Inverted or NaN ranges are skipped, use `JointLimit::validate` to reject them beforehand.
+pub fn clamp_angles(angles: &mut [[f32; 2]; 21], limits: &JointLimits) -> [bool; 21] {
+    let mut clamped = [false; 21];
+    for (i, xz) in angles.iter_mut().enumerate() {
+        let limit = match limits.get(i) {
+            Some(limit) => limit,
+            None => continue,
+        };
+        let ranges = [
+            (limit.flexion_min, limit.flexion_max),
+            (limit.abduction_min, limit.abduction_max),
+        ];
+        for (angle, (min, max)) in xz.iter_mut().zip(ranges.iter()) {
+            if angle.is_nan() || min.is_nan() || max.is_nan() || min > max {
+                continue;
+            }
+            let value = angle.clamp(*min, *max);
+            if value != *angle {
+                *angle = value;
+                clamped[i] = true;
+            }
+        }
+    }
+    return clamped;
+}
+
+/// Normalizes finger angles to curl (0 straight, 1 fully bent) and spread (0 min, 1 max) per finger.
+/// The ranges are expected to pass FingerRange::validate, an empty range yields NaN.
+pub fn curl_spread(angles: &[[f32; 2]; 21], ranges: &[FingerRange; 5]) -> [[f32; 2]; 5] {
+    let mut result = [[f32::NAN; 2]; 5];
+    for (i, finger) in FINGERS.iter().enumerate() {
+        let range = ranges[i];
+        let curl = angles[finger[0]][0] + angles[finger[1]][0] + angles[finger[2]][0];
+        result[i][0] = normalize(curl, range.curl_min, range.curl_max);
+        result[i][1] = normalize(angles[finger[0]][1], range.spread_min, range.spread_max);
+    }
+    return result;
+}
+
+/// Maps value from min..max to 0..1.
+pub(crate) fn normalize(value: f32, min: f32, max: f32) -> f32 {
+    return ((value - min) / (max - min)).clamp(0.0, 1.0);
+}
+
 /// Converts data to Vector3s.
-fn to_vectors(hand: &[[f32; 3]]) -> [Vector3; 21] {
+pub(crate) fn to_vectors(hand: &[[f32; 3]]) -> [Vector3; 21] {
     let mut data: [Vector3; 21] = [Vector3::ZERO; 21];
     for i in 0..21 {
         data[i] = Vector3::from_array(hand[i]);
@@ -36,7 +254,7 @@ fn to_vectors(hand: &[[f32; 3]]) -> [Vector3; 21] {
 }
 
 /// Sets hand origin to wrist.
-fn set_hand_origin(data: &mut [Vector3; 21]) {
+pub(crate) fn set_hand_origin(data: &mut [Vector3; 21]) {
     let offset = data[0];
     for i in 0..21 {
         data[i] -= offset;
@@ -59,8 +277,19 @@ fn angles_to_quaternions(angles: &[[f32;2]; 21], rotation_data: &mut [Quaternion
     }
 }
 
-/// Projects finger points on plane, then calculates finger x-angles.
+/// Adds the thumb twist to the thumb cmc rotation.
+fn thumb_rotation(data: &[Vector3; 21], rotation_data: &mut [Quaternion; 21]) {
+    let twist = thumb_twist(data);
+    if !twist.is_nan() {
+        rotation_data[1] *= Quaternion::from_rotation_y(twist);
+    }
+}
+
+/// Calculates signed finger x-angles around the hinge axis of every finger.
+/// Flexion towards the palm is positive, hyperextension negative.
 fn x_angles(data: &[Vector3; 21], angles: &mut [[f32; 2]; 21]) {
+    // the palm normal points to the back of the hand
+    let palm_side = palm_normal(data) * -1.0;
     for idx in 0..5 {
         // reference finger
         let mut finger: [Vector3; 5] = [Vector3::ZERO; 5];
@@ -69,35 +298,42 @@ fn x_angles(data: &[Vector3; 21], angles: &mut [[f32; 2]; 21]) {
             finger[i + 1] = data[*j];
         }
 
-        // straighten finger
-        let plane = Plane::from_vecs(finger[0], finger[1], finger[4], [0, 1, 2]);
-        for i in 0..5 {
-            finger[i] = plane.project(finger[i]);
-        }
-
-        // calculate angles
+        // calculate angles within the bending plane
+        let hinge = hinge_axis(data, &finger, palm_side, idx == 0);
         for (i, joint) in JOINTS.iter().enumerate() {
-            let joint_tail: Vector3 = finger[joint[1]] - finger[joint[0]];
-            let joint_head: Vector3 = finger[joint[2]] - finger[joint[1]];
-            angles[FINGERS[idx][i]][0] = joint_tail.angle(joint_head);
+            let joint_tail = project_on_plane(finger[joint[1]] - finger[joint[0]], hinge);
+            let joint_head = project_on_plane(finger[joint[2]] - finger[joint[1]], hinge);
+            angles[FINGERS[idx][i]][0] = signed_angle(joint_tail, joint_head, hinge);
         }
     }
 }
 
+/// Axis a finger bends around, oriented so bending towards the palm is a positive rotation.
+/// Fingers bend perpendicular to the palm, the thumb within the plane spanned by its joints
+/// and flexes across the palm towards the pinky.
+fn hinge_axis(data: &[Vector3; 21], finger: &[Vector3; 5], palm_side: Vector3, thumb: bool) -> Vector3 {
+    if !thumb {
+        return (finger[1] - finger[0]).cross(palm_side).normalize();
+    }
+    let across = (data[17] - data[5]).normalize() + palm_side;
+    let reference = (finger[2] - finger[1]).cross(across).normalize();
+    // a straight thumb doesn't span a plane
+    let normal = (finger[2] - finger[1]).cross(finger[4] - finger[2]).normalize();
+    if normal.length().is_nan() {
+        return reference;
+    }
+    if normal.dot(reference) < 0.0 {
+        return normal * -1.0;
+    }
+    return normal;
+}
+
 /// Project finger mcps on a vector between index and pinky mcp.
 /// Create circles around the mcps circles facing in the direction of vectors depending on the palm.
 /// Searching for the closest point on the circle to the fingers dip and calculate the angle.
-/// Thumb gets projected on a plane between thumb mcp, index mcp and wrist to calculate the z-angle.
+/// Thumb z-angles are calculated separately, see thumb_z_angles.
 fn z_angles(data: &[Vector3; 21], angles: &mut [[f32; 2]; 21]) {
-    // approx thumb z-angle
-    let thumb_plane = Plane::from_vecs(data[0], data[1], data[5], [0, 1, 2]);
-    let mut thumb = [Vector3::ZERO; 3];
-    for (i, vector) in [data[1], data[5], data[2]].iter().enumerate() {
-        thumb[i] = thumb_plane.project(*vector);
-    }
-    let thumb_joint_tail = thumb[0] - thumb[1];
-    let thumb_joint_head = thumb[0] - thumb[2];
-    angles[1][1] = thumb_joint_tail.angle(thumb_joint_head);
+    thumb_z_angles(data, angles);
 
     // approx other finger z-angles
     // get references at first
@@ -151,17 +387,87 @@ fn z_angles(data: &[Vector3; 21], angles: &mut [[f32; 2]; 21]) {
     }
 }
 
+/// Signed thumb abduction at the cmc and lateral deviation at the mcp.
+/// The cmc angle is measured around the palm normal between the index and thumb metacarpal,
+/// the mcp angle is the deviation of the proximal phalanx from the thumbs bending plane.
+fn thumb_z_angles(data: &[Vector3; 21], angles: &mut [[f32; 2]; 21]) {
+    let palm_normal = palm_normal(data);
+    let index_dir = project_on_plane(data[5] - data[1], palm_normal);
+    let metacarpal_dir = project_on_plane(data[2] - data[1], palm_normal);
+    angles[1][1] = signed_angle(index_dir, metacarpal_dir, palm_normal);
+
+    let thumb_normal = thumb_normal(data);
+    let phalanx_dir = (data[3] - data[2]).normalize();
+    angles[2][1] = phalanx_dir.dot(thumb_normal).clamp(-1.0, 1.0).asin();
+}
+
+/// Thumb opposition as twist around the metacarpal.
+/// Angle between the palm normal and the normal of the thumbs bending plane,
+/// zero while the thumb bends within the palm plane.
+fn thumb_twist(data: &[Vector3; 21]) -> f32 {
+    let axis = (data[2] - data[1]).normalize();
+    let palm_normal = project_on_plane(palm_normal(data), axis);
+    let thumb_normal = project_on_plane(thumb_normal(data), axis);
+    return signed_angle(palm_normal, thumb_normal, axis);
+}
+
+/// Normal of the palm spanned by wrist, index and pinky mcp.
+fn palm_normal(data: &[Vector3; 21]) -> Vector3 {
+    return (data[5] - data[0]).cross(data[17] - data[0]).normalize();
+}
+
+/// Normal of the plane the thumb bends in.
+fn thumb_normal(data: &[Vector3; 21]) -> Vector3 {
+    return (data[2] - data[1]).cross(data[4] - data[2]).normalize();
+}
+
+fn project_on_plane(vec: Vector3, normal: Vector3) -> Vector3 {
+    return vec - normal * vec.dot(normal);
+}
+
+/// Angle from a to b around the axis.
+fn signed_angle(a: Vector3, b: Vector3, axis: Vector3) -> f32 {
+    return axis.dot(a.cross(b)).atan2(a.dot(b));
+}
+
 /// Calculate wrist rotation
 fn hand_rotation(data: &[Vector3; 21], rotation_data: &mut [Quaternion; 21]) {
-    let normal = (data[5]-data[1]).normalize();
-    let binormal = (data[13]-data[5]).normalize();
+    rotation_data[0] = wrist_rotation(data[5]-data[1], data[13]-data[5]);
+}
+
+/// Wrist rotation from a direction towards the fingers and the knuckle line as binormal.
+fn wrist_rotation(normal: Vector3, binormal: Vector3) -> Quaternion {
+    let normal = normal.normalize();
+    let binormal = binormal.normalize();
     let tangent = (binormal.cross(normal)).normalize();
-    rotation_data[0] = Quaternion::from_rotation_axes(tangent, normal, binormal);
+    return Quaternion::from_rotation_axes(tangent, normal, binormal);
 }
 
 #[cfg(test)]
-mod test {
+pub(crate) mod test {
     use cgt_math::{Quaternion, Vector3};
+
+    /// Right hand facing the camera with every finger joint bent by `curl` radians towards the palm, negative values hyperextend, the thumb stays straight.
+    pub(crate) fn synthetic_hand(curl: f32) -> Vec<[f32; 3]> {
+        let wrist = Vector3::new(0.0, 0.07, 0.0);
+        let bases = [[0.03, 0.05], [0.037, -0.006], [0.012, -0.003], [-0.003, 0.004], [-0.023, 0.014]];
+        let lengths = [0.035, 0.03, 0.025, 0.022];
+        let mut hand = vec![wrist.to_array(); 21];
+        for (i, base) in bases.iter().enumerate() {
+            let mcp = Vector3::new(base[0], base[1], 0.0);
+            let dir = (mcp - wrist).normalize();
+            let angle = if i == 0 { 0.0 } else { curl };
+            let mut position = mcp;
+            hand[1 + i * 4] = position.to_array();
+            for j in 1..4 {
+                let bent = dir * (angle * j as f32).cos() - Vector3::Z * (angle * j as f32).sin();
+                position += bent * lengths[j];
+                hand[1 + i * 4 + j] = position.to_array();
+            }
+        }
+        return hand;
+    }
+
     #[test]
     fn impl_test() {
         let hand_data = [[-0.012344579212367535, 0.07004635035991669, 0.020521901547908783], [0.018071463331580162, 0.047368425875902176, 0.010523390956223011], [0.03255487233400345, 0.016385573893785477, -0.0011732536368072033], [0.037621572613716125, -0.017625989392399788, -0.013580389320850372], [0.043106138706207275, -0.05177343264222145, -0.017558827996253967], [0.024736206978559494, -0.006148995831608772, 0.0019370221998542547], [0.026668652892112732, -0.03547884523868561, -0.006494760047644377], [0.02291758358478546, -0.05428066849708557, -0.011178224347531796], [0.025358645245432854, -0.07094687223434448, -0.03112722560763359], [0.0005194954574108124, -0.0025673473719507456, 0.005111261270940304], [0.003210199996829033, -0.03972770646214485, -0.004665873944759369], [-0.002873774617910385, -0.06354730576276779, -0.018868273124098778], [0.004980511963367462, -0.08323581516742706, -0.030609922483563423], [-0.01502157561480999, 0.0035259551368653774, -0.0005771743599325418], [-0.018982525914907455, -0.03117505833506584, -0.007334231864660978], [-0.01772765815258026, -0.05211472511291504, -0.014353149570524693], [-0.013936810195446014, -0.07475992292165756, -0.028724966570734978], [-0.03519390895962715, 0.01385025680065155, -0.0037085190415382385], [-0.03892548382282257, -0.008683949708938599, -0.00501153664663434], [-0.03714082017540932, -0.02858530357480049, -0.010903152637183666], [-0.03820459172129631, -0.048742808401584625, -0.025562860071659088]];
@@ -179,6 +485,7 @@ mod test {
         // check if rotations have been calculated
         let mut rotation_data = [Quaternion::NAN; 21];
         super::angles_to_quaternions(&angles, &mut rotation_data);
+        super::thumb_rotation(&data, &mut rotation_data);
         super::hand_rotation(&data, &mut rotation_data);
 
         for finger in super::FINGERS {
@@ -186,6 +493,97 @@ mod test {
                 assert!(rotation_data[finger[i]].is_finite());
             }
         }
+
+        // check if the thumb got solved on all axes
+        assert!(!angles[1][1].is_nan() && !angles[2][1].is_nan());
+        assert!(super::thumb_twist(&data).is_finite());
+
+        // check if limits clamp and flag joints
+        let mut limited = angles;
+        let mut limits = super::JointLimits::default();
+        limits.pip.flexion_max = 0.0;
+        let clamped = super::clamp_angles(&mut limited, &limits);
+        assert!(clamped[6] && limited[6][0] == 0.0);
+        assert!(!clamped[0] && !clamped[4]);
+
+        // check if curl and spread are normalized
+        let curl_spread = super::curl_spread(&angles, &super::FINGER_RANGES);
+        for xz in curl_spread.iter() {
+            assert!(xz[0] >= 0.0 && xz[0] <= 1.0);
+            assert!(xz[1] >= 0.0 && xz[1] <= 1.0);
+        }
     }
-}
+    #[test]
+    fn inverted_limits_test() {
+        let angles = super::angles(&synthetic_hand(0.6));
+        let mut limits = super::JointLimits::default();
+        limits.pip.flexion_min = 1.0;
+        limits.pip.flexion_max = 0.5;
+        limits.dip.abduction_max = f32::NAN;
+        assert!(limits.pip.validate().is_err());
+        assert!(limits.dip.validate().is_err());
+        assert!(limits.mcp.validate().is_ok());
+
+        // invalid ranges are skipped instead of panicking
+        let mut limited = angles;
+        let clamped = super::clamp_angles(&mut limited, &limits);
+        assert!(!clamped[6] && !clamped[7]);
+        assert_eq!(limited[6][0], angles[6][0]);
+    }
+    #[test]
+    fn finger_range_test() {
+        // finger ranges need min below max as they are divided by their width
+        let mut range = super::FINGER_RANGES[1];
+        assert!(range.validate().is_ok());
+        range.curl_max = range.curl_min;
+        assert!(range.validate().is_err());
+        range.curl_max = f32::INFINITY;
+        assert!(range.validate().is_err());
+    }
+    #[test]
+    fn hyperextension_test() {
+        // bending towards the palm is positive, backwards negative
+        let angles = super::angles(&synthetic_hand(0.6));
+        assert!((angles[5][0] - 0.6).abs() < 1e-4 && (angles[6][0] - 0.6).abs() < 1e-4);
+        let hyperextended = synthetic_hand(-0.8);
+        let angles = super::angles(&hyperextended);
+        assert!((angles[5][0] + 0.8).abs() < 1e-4 && (angles[7][0] + 0.8).abs() < 1e-4);
+
+        // the mcp may hyperextend within its limit, pip and dip may not
+        let mut limits = super::JointLimits::default();
+        limits.mcp.abduction_min = -1.0;
+        limits.mcp.abduction_max = 1.0;
+        let mut limited = angles;
+        let clamped = super::clamp_angles(&mut limited, &limits);
+        assert!(clamped[5] && clamped[6] && clamped[7]);
+        assert_eq!([limited[5][0], limited[6][0], limited[7][0]], [-0.5, 0.0, 0.0]);
+        let (_, clamped) = super::main_limited(&synthetic_hand(-0.3), &limits);
+        assert!(!clamped[5] && clamped[6] && clamped[7]);
+    }
+    #[test]
+    fn pose_fallback_test() {
+        // the pose wrist, pinky and index of the synthetic hand, the thumb tip must not affect the wrist
+        let hand = synthetic_hand(0.0);
+        let mut pose_data = [[0.0f32; 3]; 33];
+        pose_data[15] = hand[0];
+        pose_data[17] = hand[17];
+        pose_data[19] = hand[5];
+        pose_data[21] = hand[4];
+        let rotation_data = super::from_pose(&pose_data, true);
+        let expected = super::main(&hand)[0];
+
+        let (a, b) = (rotation_data[0].to_array(), expected.to_array());
+        let dot: f32 = (0..4).map(|i| a[i] * b[i]).sum();
+        let length: f32 = (0..4).map(|i| a[i] * a[i]).sum::<f32>().sqrt() * (0..4).map(|i| b[i] * b[i]).sum::<f32>().sqrt();
+        assert!((dot / length).abs() > (0.05f32).cos());
+        pose_data[21] = [1.0, 1.0, 1.0];
+        assert_eq!(super::from_pose(&pose_data, true)[0].to_array(), a);
 
+        for finger in super::FINGERS {
+            for i in 0..3 {
+                assert!(rotation_data[finger[i]].is_finite());
+            }
+        }
+        assert!(!rotation_data[4].is_finite());
+    }
+}
@@ -1,9 +1,25 @@
-use pyo3::prelude::{pymodule, PyModule, PyResult, Python, pyfunction};
+use pyo3::prelude::{pymodule, PyModule, PyResult, Python, pyfunction, pyclass, pymethods};
 use pyo3::wrap_pyfunction;
+use pyo3::exceptions::{PyIOError, PyValueError};
+use std::collections::HashMap;
+use cgt_math::Quaternion;
 
 pub mod pose;
 pub mod hand;
 pub mod face;
+pub mod gesture;
+pub mod fit;
+pub mod skeleton;
+pub mod bvh;
+pub mod eval;
+pub mod gltf;
+pub mod csv;
+pub mod collada;
+pub mod usd;
+pub mod maya;
+pub mod opensim;
+pub mod c3d;
+pub mod session;
 
 
 #[pyfunction]
@@ -24,13 +40,24 @@ fn pose(data: Vec<[f32;3]>) -> PyResult<Vec<[f32; 4]>> {
     }
 }
 
-#[pyfunction]
-fn face(data: Vec<[f32;3]>) -> PyResult<Vec<[f32; 4]>> {
+fn _face_matrix(matrix: &Vec<[f32; 4]>) -> PyResult<[[f32; 4]; 4]> {
+    if matrix.len() != 4 {
+        return Err(PyValueError::new_err("Expected a 4x4 facial transformation matrix."));
+    }
+    return Ok([matrix[0], matrix[1], matrix[2], matrix[3]]);
+}
+
+#[pyfunction(matrix = "None")]
+fn face(data: Vec<[f32;3]>, matrix: Option<Vec<[f32; 4]>>) -> PyResult<Vec<[f32; 4]>> {
     // Exposed python function for mediapipe detection results.
-    // Input:   [[f32; 3]; 468]
-    // Output:  [[f32; 4]; 4]
-    if data.len() == 468 {
-        let rotations = face::main(&data);
+    // Input:   [[f32; 3]; 468] or [[f32; 3]; 478] including the iris,
+    //          optional row major [[f32; 4]; 4] facial transformation matrix for the head rotation
+    // Output:  [[f32; 4]; 6]
+    if data.len() == 468 || data.len() == 478 {
+        let rotations = match matrix {
+            Some(matrix) => face::main_with_matrix(&data, &_face_matrix(&matrix)?).0,
+            None => face::main(&data),
+        };
         let mut result: Vec<[f32; 4]> = Vec::new();
         for x in rotations.iter() {
             result.push(x.to_array());
@@ -38,7 +65,240 @@ fn face(data: Vec<[f32;3]>) -> PyResult<Vec<[f32; 4]>> {
         return Ok(result);
     }
     else {
-        return Ok(vec![[f32::NAN, f32::NAN, f32::NAN, f32::NAN]; 36]);
+        return Ok(vec![[f32::NAN, f32::NAN, f32::NAN, f32::NAN]; 6]);
+    }
+}
+
+#[pyfunction]
+fn face_matrix_deviation(data: Vec<[f32; 3]>, matrix: Vec<[f32; 4]>) -> PyResult<f32> {
+    // Exposed python function for the consistency check of landmarks and facial transformation matrix.
+    // Input:   [[f32; 3]; 468] or [[f32; 3]; 478], row major [[f32; 4]; 4]
+    // Output:  angle in radians between matrix and landmark based head rotation
+    let matrix = _face_matrix(&matrix)?;
+    if data.len() == 468 || data.len() == 478 {
+        return Ok(face::main_with_matrix(&data, &matrix).1);
+    }
+    else {
+        return Ok(f32::NAN);
+    }
+}
+
+#[pyfunction]
+fn face_blendshapes(data: Vec<[f32; 3]>) -> PyResult<Vec<f32>> {
+    // Exposed python function for ARKit compatible blendshapes, see BLENDSHAPES for the names.
+    // Input:   [[f32; 3]; 468] or [[f32; 3]; 478] including the iris
+    // Output:  [f32; 52]
+    if data.len() == 468 || data.len() == 478 {
+        return Ok(face::blendshapes(&data).to_vec());
+    }
+    else {
+        return Ok(vec![f32::NAN; 52]);
+    }
+}
+
+#[pyfunction]
+fn face_eye_openness(data: Vec<[f32; 3]>) -> PyResult<[f32; 2]> {
+    // Exposed python function for the eyelid distance relative to the eye width.
+    // Input:   [[f32; 3]; 468] or [[f32; 3]; 478]
+    // Output:  [f32; 2]
+    if data.len() == 468 || data.len() == 478 {
+        return Ok(face::eye_openness(&data));
+    }
+    else {
+        return Ok([f32::NAN; 2]);
+    }
+}
+
+#[pyfunction]
+fn face_brows(data: Vec<[f32; 3]>) -> PyResult<[f32; 3]> {
+    // Exposed python function for brow raise and furrow.
+    // Input:   [[f32; 3]; 468] or [[f32; 3]; 478]
+    // Output:  [raise.L, raise.R, furrow]
+    if data.len() == 468 || data.len() == 478 {
+        return Ok(face::brows(&data));
+    }
+    else {
+        return Ok([f32::NAN; 3]);
+    }
+}
+
+fn _mouth_calibration(values: HashMap<String, f32>) -> PyResult<face::MouthCalibration> {
+    let mut calibration = face::MouthCalibration::default();
+    for (key, value) in values.iter() {
+        let field = match key.as_str() {
+            "jaw_rest_angle" => &mut calibration.jaw_rest_angle,
+            "jaw_angle_scale" => &mut calibration.jaw_angle_scale,
+            "jaw_open_range" => &mut calibration.jaw_open_range,
+            "width_pucker" => &mut calibration.width_pucker,
+            "width_neutral" => &mut calibration.width_neutral,
+            "width_wide" => &mut calibration.width_wide,
+            "upper_lip_neutral" => &mut calibration.upper_lip_neutral,
+            "upper_lip_raised" => &mut calibration.upper_lip_raised,
+            "lower_lip_neutral" => &mut calibration.lower_lip_neutral,
+            "lower_lip_lowered" => &mut calibration.lower_lip_lowered,
+            _ => return Err(PyValueError::new_err(format!("Unknown mouth calibration key: {}", key))),
+        };
+        *field = *value;
+    }
+    return Ok(calibration);
+}
+
+#[pyfunction(calibration = "None")]
+fn face_mouth(data: Vec<[f32; 3]>, calibration: Option<HashMap<String, f32>>) -> PyResult<(Vec<f32>, Option<&'static str>)> {
+    // Exposed python function for calibrated mouth shapes and the viseme.
+    // Input:   [[f32; 3]; 468] or [[f32; 3]; 478], optional calibration values by name
+    // Output:  ([jaw_open, width, pucker, upper_lip, lower_lip], viseme)
+    let calibration = match calibration {
+        Some(values) => _mouth_calibration(values)?,
+        None => face::MouthCalibration::default(),
+    };
+    if data.len() == 468 || data.len() == 478 {
+        let shapes = face::mouth_shapes(&data, &calibration);
+        let values = vec![shapes.jaw_open, shapes.width, shapes.pucker, shapes.upper_lip, shapes.lower_lip];
+        return Ok((values, Some(face::VISEMES[face::viseme(&shapes)])));
+    }
+    else {
+        return Ok((vec![f32::NAN; 5], None));
+    }
+}
+
+#[pyfunction(reference = "None", aspect_ratio = "1.7777778")]
+fn face_head_pose(data: Vec<[f32; 3]>, reference: Option<Vec<[f32; 3]>>, aspect_ratio: f32) -> PyResult<Option<([f32; 4], [f32; 3], f32, f32)>> {
+    // Exposed python function for the head pose fitted against a reference like the canonical face model.
+    // Input:   [[f32; 3]; 468] or [[f32; 3]; 478], reference [[f32; 3]; 468] or None for the built-in reference face
+    // Output:  (rotation, translation, scale, rms error) or None
+    if !(data.len() == 468 || data.len() == 478) {
+        return Ok(None);
+    }
+    let pose = face::head_pose(&data, reference.as_deref(), aspect_ratio);
+    return Ok(pose.map(|x| (
+        x.rotation.to_array(),
+        [x.translation.x, x.translation.y, x.translation.z],
+        x.scale,
+        x.error,
+    )));
+}
+
+#[pyfunction(focal_length = "None", aspect_ratio = "1.7777778")]
+fn face_head_position(data: Vec<[f32; 3]>, focal_length: Option<f32>, aspect_ratio: f32) -> PyResult<([f32; 3], f32)> {
+    // Exposed python function for the head translation in meters and the distance to the camera.
+    // Input:   [[f32; 3]; 468] or [[f32; 3]; 478], focal length relative to the image width
+    // Output:  ([x, y, z], distance)
+    if !(data.len() == 468 || data.len() == 478) {
+        return Ok(([f32::NAN; 3], f32::NAN));
+    }
+    let mut camera = face::Camera::default();
+    camera.aspect_ratio = aspect_ratio;
+    if let Some(focal_length) = focal_length {
+        camera.focal_length = focal_length;
+    }
+    let position = face::head_position(&data, &camera);
+    let t = position.translation;
+    return Ok(([t.x, t.y, t.z], position.distance));
+}
+
+#[pyfunction]
+fn obj_vertices(text: &str) -> PyResult<Vec<[f32; 3]>> {
+    // Exposed python function to load vertices of the canonical face model obj.
+    return Ok(face::obj_vertices(text));
+}
+
+fn _check_face(data: &Vec<[f32; 3]>) -> PyResult<()> {
+    if data.len() == 468 || data.len() == 478 {
+        return Ok(());
+    }
+    return Err(PyValueError::new_err(format!("Expected 468 or 478 face landmarks, got {}.", data.len())));
+}
+
+#[pyclass(name = "NeutralFace")]
+struct PyNeutralFace {
+    neutral: face::NeutralFace,
+}
+
+#[pymethods]
+impl PyNeutralFace {
+    #[new]
+    fn new() -> Self {
+        PyNeutralFace { neutral: face::NeutralFace::new() }
+    }
+
+    /// Captures a resting face frame.
+    fn add_frame(&mut self, data: Vec<[f32; 3]>) -> PyResult<()> {
+        _check_face(&data)?;
+        self.neutral.add_frame(&data);
+        return Ok(());
+    }
+
+    fn frames(&self) -> usize {
+        return self.neutral.frames();
+    }
+
+    fn rotations(&self, data: Vec<[f32; 3]>) -> PyResult<Vec<[f32; 4]>> {
+        _check_face(&data)?;
+        let rotations = self.neutral.rotations(&data);
+        return Ok(rotations.iter().map(|x| x.to_array()).collect());
+    }
+
+    fn blendshapes(&self, data: Vec<[f32; 3]>) -> PyResult<Vec<f32>> {
+        _check_face(&data)?;
+        return Ok(self.neutral.blendshapes(&data).to_vec());
+    }
+
+    fn eye_openness(&self, data: Vec<[f32; 3]>) -> PyResult<[f32; 2]> {
+        _check_face(&data)?;
+        return Ok(self.neutral.eye_openness(&data));
+    }
+
+    fn brows(&self, data: Vec<[f32; 3]>) -> PyResult<[f32; 3]> {
+        _check_face(&data)?;
+        return Ok(self.neutral.brows(&data));
+    }
+
+    fn mouth(&self, data: Vec<[f32; 3]>) -> PyResult<(Vec<f32>, &'static str)> {
+        _check_face(&data)?;
+        let shapes = self.neutral.mouth_shapes(&data);
+        let values = vec![shapes.jaw_open, shapes.width, shapes.pucker, shapes.upper_lip, shapes.lower_lip];
+        return Ok((values, face::VISEMES[face::viseme(&shapes)]));
+    }
+
+    fn serialize(&self) -> String {
+        return self.neutral.serialize();
+    }
+
+    #[staticmethod]
+    fn deserialize(text: &str) -> PyResult<Self> {
+        let neutral = face::NeutralFace::deserialize(text).map_err(PyValueError::new_err)?;
+        return Ok(PyNeutralFace { neutral });
+    }
+}
+
+#[pyclass(name = "EyeTracker")]
+struct PyEyeTracker {
+    tracker: face::EyeTracker,
+}
+
+#[pymethods]
+impl PyEyeTracker {
+    #[new]
+    #[args(rise_rate = "0.5", fall_rate = "0.01")]
+    fn new(rise_rate: f32, fall_rate: f32) -> Self {
+        PyEyeTracker { tracker: face::EyeTracker::new(rise_rate, fall_rate) }
+    }
+
+    /// Returns the openness of the left and right eye in the range 0..1.
+    fn update(&mut self, data: Vec<[f32; 3]>) -> [f32; 2] {
+        if data.len() == 468 || data.len() == 478 {
+            return self.tracker.update(&data);
+        }
+        return [f32::NAN; 2];
+    }
+
+    fn baseline(&self) -> [f32; 2] {
+        return self.tracker.baseline();
+    }
+
+    fn reset(&mut self) {
+        self.tracker.reset();
     }
 }
 
@@ -56,6 +316,20 @@ fn _hand(data: &Vec<[f32; 3]>) -> Vec<[f32; 4]> {
     }
 }
 
+fn _hand_from_pose(pose: &Vec<[f32; 3]>, left: bool) -> Vec<[f32; 4]> {
+    if pose.len() == 33 {
+        let rotations = hand::from_pose(&pose, left);
+        let mut result: Vec<[f32; 4]> = Vec::new();
+        for x in rotations.iter() {
+            result.push(x.to_array());
+        }
+        return result;
+    }
+    else {
+        return vec![[f32::NAN, f32::NAN, f32::NAN, f32::NAN]; 21];
+    }
+}
+
 
 #[pyfunction]
 fn hand(data: Vec<[f32;3]>) -> PyResult<Vec<[f32; 4]>> {
@@ -65,6 +339,14 @@ fn hand(data: Vec<[f32;3]>) -> PyResult<Vec<[f32; 4]>> {
     return Ok(_hand(&data));
 }
 
+#[pyfunction]
+fn hand_from_pose(data: Vec<[f32; 3]>, left: bool) -> PyResult<Vec<[f32; 4]>> {
+    // Exposed python function for coarse hand rotations from pose landmarks.
+    // Input:   [[f32; 3]; 33]
+    // Output:  [[f32; 4]; 21]
+    return Ok(_hand_from_pose(&data, left));
+}
+
 #[pyfunction]
 fn hands(data: Vec<Vec<[f32; 3]>>) -> PyResult<Vec<Vec<[f32; 4]>>> {
     // Exposed python function for mediapipe detection results.
@@ -85,13 +367,19 @@ fn holistic(data: Vec<Vec<[f32; 3]>>) -> PyResult<Vec<Vec<[f32; 4]>>> {
     let hand_data_l = &data[2];
     let hand_data_r = &data[3];
 
-    // hand result
-    let hand_result_l = _hand(hand_data_l);
-    let hand_result_r = _hand(hand_data_r);
+    // hand result, falls back to pose landmarks if a hand hasn't been detected
+    let hand_result_l = match hand_data_l.len() {
+        21 => _hand(hand_data_l),
+        _ => _hand_from_pose(pose_data, true),
+    };
+    let hand_result_r = match hand_data_r.len() {
+        21 => _hand(hand_data_r),
+        _ => _hand_from_pose(pose_data, false),
+    };
 
     // face result
-    let mut face_result = vec![[f32::NAN, f32::NAN, f32::NAN, f32::NAN]; 4];
-    if face_data.len() == 468 {
+    let mut face_result = vec![[f32::NAN, f32::NAN, f32::NAN, f32::NAN]; 6];
+    if face_data.len() == 468 || face_data.len() == 478 {
         let face_rotations = face::main(&face_data);
         for i in 0..face_rotations.len() {
             face_result[i] = face_rotations[i].to_array();
@@ -116,14 +404,417 @@ fn holistic(data: Vec<Vec<[f32; 3]>>) -> PyResult<Vec<Vec<[f32; 4]>>> {
     return Ok(result);
 }
 
+#[pyfunction(limits = "None")]
+fn hand_limited(data: Vec<[f32; 3]>, limits: Option<Vec<[f32; 4]>>) -> PyResult<(Vec<[f32; 4]>, Vec<bool>)> {
+    // Exposed python function for joint limited hand rotations.
+    // Input:   [[f32; 3]; 21], optional [[flexion_min, flexion_max, abduction_min, abduction_max]; 6]
+    //          for thumb cmc, thumb mcp, thumb ip, mcp, pip and dip
+    // Output:  ([[f32; 4]; 21], [bool; 21])
+    let mut joint_limits = hand::JointLimits::default();
+    if let Some(limits) = limits {
+        if limits.len() != 6 {
+            return Err(PyValueError::new_err("Expected joint limits of length 6."));
+        }
+        let l: Vec<hand::JointLimit> = limits.iter().map(|x| hand::JointLimit {
+            flexion_min: x[0], flexion_max: x[1], abduction_min: x[2], abduction_max: x[3]
+        }).collect();
+        for limit in l.iter() {
+            limit.validate().map_err(PyValueError::new_err)?;
+        }
+        joint_limits = hand::JointLimits {
+            thumb_cmc: l[0], thumb_mcp: l[1], thumb_ip: l[2], mcp: l[3], pip: l[4], dip: l[5]
+        };
+    }
+
+    if data.len() == 21 {
+        let (rotations, clamped) = hand::main_limited(&data, &joint_limits);
+        let mut result: Vec<[f32; 4]> = Vec::new();
+        for x in rotations.iter() {
+            result.push(x.to_array());
+        }
+        return Ok((result, clamped.to_vec()));
+    }
+    else {
+        return Ok((vec![[f32::NAN, f32::NAN, f32::NAN, f32::NAN]; 21], vec![false; 21]));
+    }
+}
+
+#[pyfunction(ranges = "None")]
+fn hand_curls(data: Vec<[f32; 3]>, ranges: Option<Vec<[f32; 4]>>) -> PyResult<Vec<[f32; 2]>> {
+    // Exposed python function for normalized finger curl and spread.
+    // Input:   [[f32; 3]; 21], optional [[curl_min, curl_max, spread_min, spread_max]; 5]
+    // Output:  [[f32; 2]; 5]
+    let mut finger_ranges = hand::FINGER_RANGES;
+    if let Some(ranges) = ranges {
+        if ranges.len() != 5 {
+            return Err(PyValueError::new_err("Expected finger ranges of length 5."));
+        }
+        for (i, x) in ranges.iter().enumerate() {
+            finger_ranges[i] = hand::FingerRange { curl_min: x[0], curl_max: x[1], spread_min: x[2], spread_max: x[3] };
+            finger_ranges[i].validate().map_err(PyValueError::new_err)?;
+        }
+    }
+
+    if data.len() == 21 {
+        let angles = hand::angles(&data);
+        return Ok(hand::curl_spread(&angles, &finger_ranges).to_vec());
+    }
+    else {
+        return Ok(vec![[f32::NAN, f32::NAN]; 5]);
+    }
+}
+
+#[pyfunction]
+fn hand_gesture(data: Vec<[f32; 3]>) -> PyResult<Vec<f32>> {
+    // Exposed python function for gesture scores.
+    // Input:   [[f32; 3]; 21]
+    // Output:  [f32; 7]
+    if data.len() == 21 {
+        return Ok(gesture::scores(&data).to_vec());
+    }
+    else {
+        return Ok(vec![f32::NAN; 7]);
+    }
+}
+
+#[pyclass(name = "GestureTracker")]
+struct PyGestureTracker {
+    tracker: gesture::GestureTracker,
+}
+
+#[pymethods]
+impl PyGestureTracker {
+    #[new]
+    #[args(threshold = "0.6", hold_frames = "3")]
+    fn new(threshold: f32, hold_frames: usize) -> Self {
+        PyGestureTracker { tracker: gesture::GestureTracker::new(threshold, hold_frames) }
+    }
+
+    /// Returns the debounced gesture name or None.
+    fn update(&mut self, data: Vec<[f32; 3]>) -> Option<&'static str> {
+        let current = self.tracker.update(&data);
+        return current.map(|i| gesture::GESTURES[i]);
+    }
+
+    fn scores(&self) -> Vec<f32> {
+        return self.tracker.scores().to_vec();
+    }
+
+    fn reset(&mut self) {
+        self.tracker.reset();
+    }
+}
+
+fn _frames(poses: Vec<Vec<[f32; 3]>>, left_hands: Option<Vec<Vec<[f32; 3]>>>, right_hands: Option<Vec<Vec<[f32; 3]>>>, faces: Option<Vec<Vec<[f32; 3]>>>) -> PyResult<Vec<skeleton::Frame>> {
+    // Empty landmark lists mark frames without detection.
+    let count = poses.len();
+    let left_hands = left_hands.unwrap_or_else(|| vec![Vec::new(); count]);
+    let right_hands = right_hands.unwrap_or_else(|| vec![Vec::new(); count]);
+    let faces = faces.unwrap_or_else(|| vec![Vec::new(); count]);
+    if left_hands.len() != count || right_hands.len() != count || faces.len() != count {
+        return Err(PyValueError::new_err("Expected the same number of frames for poses, hands and faces."));
+    }
+
+    let mut frames: Vec<skeleton::Frame> = Vec::with_capacity(count);
+    for (((pose, left_hand), right_hand), face) in poses.into_iter().zip(left_hands).zip(right_hands).zip(faces) {
+        frames.push(skeleton::Frame { pose, left_hand, right_hand, face });
+    }
+    return Ok(frames);
+}
+
+fn _rotation_order(order: &str) -> PyResult<skeleton::RotationOrder> {
+    return order.parse::<skeleton::RotationOrder>().map_err(PyValueError::new_err);
+}
+
+fn _fps(fps: f32) -> PyResult<f32> {
+    if !fps.is_finite() || fps <= 0.0 {
+        return Err(PyValueError::new_err(format!("Expected a positive frame rate, got {}.", fps)));
+    }
+    return Ok(fps);
+}
+
+fn _smooth(poses: Vec<Vec<[f32; 3]>>, window: Option<usize>) -> PyResult<Vec<Vec<[f32; 3]>>> {
+    // Moving average of the pose landmarks if a window is passed.
+    return match window {
+        Some(0) => Err(PyValueError::new_err("Expected a smoothing window of at least one frame.")),
+        Some(window) => Ok(opensim::smooth(&poses, window)),
+        None => Ok(poses),
+    };
+}
+
+#[pyfunction(left_hands = "None", right_hands = "None", fps = "30.0", order = "\"ZXY\"", scale = "1.0")]
+fn export_bvh(poses: Vec<Vec<[f32; 3]>>, left_hands: Option<Vec<Vec<[f32; 3]>>>, right_hands: Option<Vec<Vec<[f32; 3]>>>, fps: f32, order: &str, scale: f32) -> PyResult<String> {
+    // Exposed python function for BVH export of pose sequences.
+    // Input:   [[[f32; 3]; 33]; n], optional [[[f32; 3]; 21]; n] per hand
+    // Output:  BVH file content
+    let hands = left_hands.is_some() || right_hands.is_some();
+    let frames = _frames(poses, left_hands, right_hands, None)?;
+    let animation = skeleton::animate(&frames, _fps(fps)?, hands);
+    return Ok(bvh::write(&animation, _rotation_order(order)?, scale));
+}
+
+#[pyfunction(left_hands = "None", right_hands = "None", fps = "30.0", scale = "1.0")]
+fn export_gltf(path: &str, poses: Vec<Vec<[f32; 3]>>, left_hands: Option<Vec<Vec<[f32; 3]>>>, right_hands: Option<Vec<Vec<[f32; 3]>>>, fps: f32, scale: f32) -> PyResult<()> {
+    // Exposed python function for glTF export of pose sequences.
+    // Input:   output path (.gltf or .glb), [[[f32; 3]; 33]; n], optional [[[f32; 3]; 21]; n] per hand
+    // Output:  None, a .gltf gets its buffer written to a .bin next to it
+    let hands = left_hands.is_some() || right_hands.is_some();
+    let frames = _frames(poses, left_hands, right_hands, None)?;
+    let animation = skeleton::animate(&frames, _fps(fps)?, hands);
+    return gltf::save(std::path::Path::new(path), &animation, scale).map_err(|err| match err.kind() {
+        std::io::ErrorKind::InvalidInput => PyValueError::new_err(err.to_string()),
+        _ => PyIOError::new_err(err.to_string()),
+    });
+}
+
+#[pyfunction(left_hands = "None", right_hands = "None", fps = "30.0", scale = "1.0")]
+fn export_collada(poses: Vec<Vec<[f32; 3]>>, left_hands: Option<Vec<Vec<[f32; 3]>>>, right_hands: Option<Vec<Vec<[f32; 3]>>>, fps: f32, scale: f32) -> PyResult<String> {
+    // Exposed python function for Collada export of pose sequences.
+    // Input:   [[[f32; 3]; 33]; n], optional [[[f32; 3]; 21]; n] per hand
+    // Output:  .dae file content
+    let hands = left_hands.is_some() || right_hands.is_some();
+    let frames = _frames(poses, left_hands, right_hands, None)?;
+    let animation = skeleton::animate(&frames, _fps(fps)?, hands);
+    return Ok(collada::write(&animation, scale));
+}
+
+#[pyfunction(left_hands = "None", right_hands = "None", faces = "None", fps = "30.0", scale = "1.0")]
+fn export_usd(poses: Vec<Vec<[f32; 3]>>, left_hands: Option<Vec<Vec<[f32; 3]>>>, right_hands: Option<Vec<Vec<[f32; 3]>>>, faces: Option<Vec<Vec<[f32; 3]>>>, fps: f32, scale: f32) -> PyResult<String> {
+    // Exposed python function for USD export of pose sequences.
+    // Input:   [[[f32; 3]; 33]; n], optional [[[f32; 3]; 21]; n] per hand, optional [[[f32; 3]; 468]; n]
+    // Output:  .usda file content
+    let hands = left_hands.is_some() || right_hands.is_some();
+    let frames = _frames(poses, left_hands, right_hands, faces)?;
+    let animation = skeleton::animate(&frames, _fps(fps)?, hands);
+    return Ok(usd::write(&animation, scale));
+}
+
+#[pyfunction(left_hands = "None", right_hands = "None", faces = "None", fps = "30.0", order = "\"ZYX\"", scale = "1.0", tolerance = "None")]
+fn export_maya_anim(poses: Vec<Vec<[f32; 3]>>, left_hands: Option<Vec<Vec<[f32; 3]>>>, right_hands: Option<Vec<Vec<[f32; 3]>>>, faces: Option<Vec<Vec<[f32; 3]>>>, fps: f32, order: &str, scale: f32, tolerance: Option<f32>) -> PyResult<String> {
+    // Exposed python function for Maya .anim export of pose sequences.
+    // Input:   [[[f32; 3]; 33]; n], optional [[[f32; 3]; 21]; n] per hand, optional [[[f32; 3]; 468]; n]
+    // Output:  .anim file content
+    let order = _rotation_order(order)?;
+    let hands = left_hands.is_some() || right_hands.is_some();
+    let frames = _frames(poses, left_hands, right_hands, faces)?;
+    let animation = skeleton::animate(&frames, _fps(fps)?, hands);
+    return Ok(maya::write(&animation, order, scale, tolerance));
+}
+
+#[pyfunction(fps = "30.0", window = "None", name = "\"mediapipe.trc\"")]
+fn export_trc(poses: Vec<Vec<[f32; 3]>>, fps: f32, window: Option<usize>, name: &str) -> PyResult<String> {
+    // Exposed python function for OpenSim marker export of pose landmarks.
+    // Input:   [[[f32; 3]; 33]; n], moving average window in frames
+    // Output:  .trc file content
+    let poses = _smooth(poses, window)?;
+    return Ok(opensim::write_trc(&poses, _fps(fps)?, name));
+}
+
+#[pyfunction(fps = "30.0", window = "None", name = "\"mediapipe.mot\"")]
+fn export_mot(poses: Vec<Vec<[f32; 3]>>, fps: f32, window: Option<usize>, name: &str) -> PyResult<String> {
+    // Exposed python function for OpenSim motion export of joint angles.
+    // Input:   [[[f32; 3]; 33]; n], moving average window in frames
+    // Output:  .mot file content
+    let poses = _smooth(poses, window)?;
+    let animation = skeleton::animate(&_frames(poses, None, None, None)?, _fps(fps)?, false);
+    return Ok(opensim::write_mot(&animation, name));
+}
+
+#[pyfunction(visibility = "None", fps = "30.0")]
+fn export_c3d(path: &str, poses: Vec<Vec<[f32; 3]>>, visibility: Option<Vec<Vec<f32>>>, fps: f32) -> PyResult<()> {
+    // Exposed python function for C3D export of pose landmarks and joint angles.
+    // Input:   output path, [[[f32; 3]; 33]; n], optional [[f32; 33]; n]
+    // Output:  None
+    let visibility = visibility.unwrap_or_default();
+    let animation = skeleton::animate(&_frames(poses.clone(), None, None, None)?, _fps(fps)?, false);
+    let c3d = c3d::write(&poses, &visibility, &animation).map_err(PyValueError::new_err)?;
+    return std::fs::write(path, c3d).map_err(|err| PyIOError::new_err(err.to_string()));
+}
+
+#[pyfunction]
+fn read_landmarks_csv(text: &str) -> PyResult<(Vec<Vec<[f32; 3]>>, Vec<Vec<f32>>)> {
+    // Exposed python function to read landmark sequences.
+    // Input:   CSV content with frame, landmark, x, y, z, visibility columns
+    // Output:  landmarks and visibility per frame, empty for frames without rows
+    let result = csv::read_landmarks(text).map_err(PyValueError::new_err)?;
+    return Ok((result.landmarks, result.visibility));
+}
+
+#[pyfunction(visibility = "None")]
+fn write_landmarks_csv(landmarks: Vec<Vec<[f32; 3]>>, visibility: Option<Vec<Vec<f32>>>) -> PyResult<String> {
+    // Exposed python function to write landmark sequences.
+    // Input:   [[[f32; 3]; n]; frames], optional [[f32; n]; frames]
+    // Output:  CSV content
+    let landmarks = csv::Landmarks { landmarks, visibility: visibility.unwrap_or_default() };
+    return Ok(csv::write_landmarks(&landmarks));
+}
+
+#[pyfunction(pose = "None", left_hand = "None", right_hand = "None", face = "None", order = "None")]
+fn rotations_csv(pose: Option<Vec<Vec<[f32; 4]>>>, left_hand: Option<Vec<Vec<[f32; 4]>>>, right_hand: Option<Vec<Vec<[f32; 4]>>>, face: Option<Vec<Vec<[f32; 4]>>>, order: Option<&str>) -> PyResult<String> {
+    // Exposed python function to write solver results.
+    // Input:   rotations per frame as returned by pose, hand and face
+    // Output:  CSV content with frame, bone, w, x, y, z or frame, bone, x, y, z euler columns
+    let order = match order {
+        Some(order) => Some(_rotation_order(order)?),
+        None => None,
+    };
+    let pose_names = csv::pose_names();
+    let tracks = [
+        (pose, &pose_names[..], ""),
+        (left_hand, &skeleton::HAND_BONES[..], ".L"),
+        (right_hand, &skeleton::HAND_BONES[..], ".R"),
+        (face, &skeleton::FACE_BONES[..], ""),
+    ];
+    let frame_count = tracks.iter().map(|x| x.0.as_ref().map_or(0, |x| x.len())).max().unwrap_or(0);
+    let mut frames: Vec<Vec<(String, Quaternion)>> = vec![Vec::new(); frame_count];
+    for (track, names, suffix) in tracks.iter() {
+        if let Some(track) = track {
+            for (frame, rotations) in track.iter().enumerate() {
+                let rotations: Vec<Quaternion> = rotations.iter().map(|x| skeleton::from_array(*x)).collect();
+                frames[frame].extend(csv::named_rotations(names, &rotations, suffix));
+            }
+        }
+    }
+    return Ok(csv::write_rotations(&frames, order));
+}
+
+#[pyfunction(fps = "30.0", time_offset = "0.0", bone_map = "None")]
+fn evaluate_bvh(poses: Vec<Vec<[f32; 3]>>, reference: &str, fps: f32, time_offset: f32, bone_map: Option<HashMap<String, String>>) -> PyResult<(f32, HashMap<String, [f32; 3]>, String)> {
+    // Exposed python function to compare pose sequences with BVH recordings.
+    // Input:   [[[f32; 3]; 33]; n], BVH file content, optional {bone: reference bone}
+    // Output:  MPJPE, {bone: [direction error, rotation error, max rotation error]}, report
+    let reference = bvh::parse(reference).map_err(PyValueError::new_err)?;
+    let solved = skeleton::animate(&_frames(poses, None, None, None)?, _fps(fps)?, false);
+    let bone_map: Vec<(String, String)> = match bone_map {
+        Some(map) => map.into_iter().collect(),
+        None => eval::default_bone_map(&reference.skeleton),
+    };
+
+    let report = eval::evaluate(&solved, &reference, &bone_map, time_offset).map_err(PyValueError::new_err)?;
+    let mut errors: HashMap<String, [f32; 3]> = HashMap::new();
+    for bone in report.bones.iter() {
+        errors.insert(bone.name.clone(), [bone.direction_error, bone.rotation_error, bone.max_rotation_error]);
+    }
+    return Ok((report.mpjpe, errors, report.to_text()));
+}
+
+
+type _SessionFrame = (f64, Option<Vec<Vec<f32>>>, Option<Vec<Vec<f32>>>, Option<Vec<Vec<f32>>>, Option<Vec<Vec<f32>>>);
+
+fn _detection(data: Option<Vec<Vec<f32>>>) -> PyResult<Option<session::Detection>> {
+    // Landmarks as [x, y, z] or [x, y, z, visibility].
+    let data = match data {
+        Some(data) => data,
+        None => return Ok(None),
+    };
+    let mut detection = session::Detection { landmarks: Vec::with_capacity(data.len()), visibility: Vec::with_capacity(data.len()) };
+    for landmark in data.iter() {
+        if landmark.len() != 3 && landmark.len() != 4 {
+            return Err(PyValueError::new_err("Expected landmarks as [x, y, z] or [x, y, z, visibility]."));
+        }
+        detection.landmarks.push([landmark[0], landmark[1], landmark[2]]);
+        detection.visibility.push(landmark.get(3).copied().unwrap_or(1.0));
+    }
+    return Ok(Some(detection));
+}
+
+fn _session_frame(frame: _SessionFrame) -> PyResult<session::SessionFrame> {
+    let (timestamp, pose, face, left_hand, right_hand) = frame;
+    return Ok(session::SessionFrame {
+        timestamp,
+        pose: _detection(pose)?,
+        face: _detection(face)?,
+        left_hand: _detection(left_hand)?,
+        right_hand: _detection(right_hand)?,
+    });
+}
+
+#[pyclass(name = "SessionWriter")]
+struct PySessionWriter {
+    writer: session::SessionWriter,
+}
+
+#[pymethods]
+impl PySessionWriter {
+    /// Appends to an existing session or creates a new one.
+    #[new]
+    fn new(path: &str) -> PyResult<Self> {
+        let writer = session::SessionWriter::open(std::path::Path::new(path)).map_err(|err| PyIOError::new_err(err.to_string()))?;
+        return Ok(PySessionWriter { writer });
+    }
+
+    #[args(pose = "None", face = "None", left_hand = "None", right_hand = "None")]
+    fn write(&mut self, timestamp: f64, pose: Option<Vec<Vec<f32>>>, face: Option<Vec<Vec<f32>>>, left_hand: Option<Vec<Vec<f32>>>, right_hand: Option<Vec<Vec<f32>>>) -> PyResult<()> {
+        let frame = _session_frame((timestamp, pose, face, left_hand, right_hand))?;
+        return self.writer.write(&frame).map_err(|err| PyIOError::new_err(err.to_string()));
+    }
+
+    fn flush(&mut self) -> PyResult<()> {
+        return self.writer.flush().map_err(|err| PyIOError::new_err(err.to_string()));
+    }
+}
+
+#[pyfunction]
+fn read_session(path: &str) -> PyResult<Vec<(f64, Option<Vec<[f32; 4]>>, Option<Vec<[f32; 4]>>, Option<Vec<[f32; 4]>>, Option<Vec<[f32; 4]>>)>> {
+    // Exposed python function to replay recorded sessions.
+    // Input:   session path
+    // Output:  [(timestamp, pose, face, left_hand, right_hand); n], landmarks as [x, y, z, visibility] or None
+    let data = std::fs::read(path).map_err(|err| PyIOError::new_err(err.to_string()))?;
+    let frames = session::decode(&data).map_err(PyValueError::new_err)?;
+    let landmarks = |x: &Option<session::Detection>| x.as_ref().map(|x| {
+        x.landmarks.iter().zip(x.visibility.iter()).map(|(p, v)| [p[0], p[1], p[2], *v]).collect::<Vec<[f32; 4]>>()
+    });
+    return Ok(frames.iter().map(|x| (x.timestamp, landmarks(&x.pose), landmarks(&x.face), landmarks(&x.left_hand), landmarks(&x.right_hand))).collect());
+}
+
+#[pyfunction]
+fn write_session(path: &str, frames: Vec<_SessionFrame>) -> PyResult<()> {
+    // Exposed python function to save complete sessions.
+    // Input:   session path, [(timestamp, pose, face, left_hand, right_hand); n], landmarks as [x, y, z] or [x, y, z, visibility] or None
+    // Output:  None
+    let frames = frames.into_iter().map(_session_frame).collect::<PyResult<Vec<session::SessionFrame>>>()?;
+    return session::save(std::path::Path::new(path), &frames).map_err(|err| PyIOError::new_err(err.to_string()));
+}
 
 #[pymodule]
 fn mediapipe_rotations(_py: Python<'_>, m: &PyModule) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(pose, m)?)?;
     m.add_function(wrap_pyfunction!(face, m)?)?;
+    m.add_function(wrap_pyfunction!(face_matrix_deviation, m)?)?;
+    m.add_function(wrap_pyfunction!(face_blendshapes, m)?)?;
+    m.add("BLENDSHAPES", face::BLENDSHAPES.to_vec())?;
+    m.add_function(wrap_pyfunction!(face_eye_openness, m)?)?;
+    m.add_class::<PyEyeTracker>()?;
+    m.add_function(wrap_pyfunction!(face_brows, m)?)?;
+    m.add_function(wrap_pyfunction!(face_mouth, m)?)?;
+    m.add("VISEMES", face::VISEMES.to_vec())?;
+    m.add_class::<PyNeutralFace>()?;
+    m.add_function(wrap_pyfunction!(face_head_pose, m)?)?;
+    m.add_function(wrap_pyfunction!(obj_vertices, m)?)?;
+    m.add_function(wrap_pyfunction!(face_head_position, m)?)?;
     m.add_function(wrap_pyfunction!(hand, m)?)?;
+    m.add_function(wrap_pyfunction!(hand_from_pose, m)?)?;
     m.add_function(wrap_pyfunction!(hands, m)?)?;
     m.add_function(wrap_pyfunction!(holistic, m)?)?;
+    m.add_function(wrap_pyfunction!(hand_limited, m)?)?;
+    m.add_function(wrap_pyfunction!(hand_curls, m)?)?;
+    m.add_function(wrap_pyfunction!(hand_gesture, m)?)?;
+    m.add_class::<PyGestureTracker>()?;
+    m.add_function(wrap_pyfunction!(export_bvh, m)?)?;
+    m.add_function(wrap_pyfunction!(evaluate_bvh, m)?)?;
+    m.add_function(wrap_pyfunction!(export_gltf, m)?)?;
+    m.add_function(wrap_pyfunction!(export_collada, m)?)?;
+    m.add_function(wrap_pyfunction!(export_usd, m)?)?;
+    m.add_function(wrap_pyfunction!(export_maya_anim, m)?)?;
+    m.add_function(wrap_pyfunction!(export_trc, m)?)?;
+    m.add_function(wrap_pyfunction!(export_mot, m)?)?;
+    m.add_function(wrap_pyfunction!(export_c3d, m)?)?;
+    m.add_function(wrap_pyfunction!(read_landmarks_csv, m)?)?;
+    m.add_function(wrap_pyfunction!(write_landmarks_csv, m)?)?;
+    m.add_function(wrap_pyfunction!(rotations_csv, m)?)?;
+    m.add_class::<PySessionWriter>()?;
+    m.add_function(wrap_pyfunction!(read_session, m)?)?;
+    m.add_function(wrap_pyfunction!(write_session, m)?)?;
     Ok(())
 }
 
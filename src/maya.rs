@@ -0,0 +1,130 @@
+use crate::skeleton::{self, Animation, RotationOrder};
+use std::fmt::Write;
+
+/// Writes the animation as Maya ASCII .anim curves, a translation curve per axis for the root and a rotation curve per axis for every bone.
+/// Angles are in degrees and unrolled between frames, the rotation order has to match the rotate order of the joints in reverse,
+/// so ZYX matches Maya's default xyz. Keys of a curve are reduced to the given tolerance in degrees or units if one is passed.
+pub fn write(animation: &Animation, order: RotationOrder, scale: f32, tolerance: Option<f32>) -> String {
+    let bones = &animation.skeleton.bones;
+    let frame_count = animation.frame_count();
+    let fps = (1000.0 / animation.frame_time).round() / 1000.0;
+
+    let mut text = String::from("animVersion 1.1;\nmayaVersion 2020;\n");
+    writeln!(text, "timeUnit {};\nlinearUnit m;\nangularUnit deg;", time_unit(fps)).unwrap();
+    writeln!(text, "startTime 0;\nendTime {};", frame_count.saturating_sub(1)).unwrap();
+
+    // euler angles in degrees per bone and frame, unrolled along the frames
+    let mut angles: Vec<Vec<[f32; 3]>> = vec![Vec::with_capacity(frame_count); bones.len()];
+    for rotations in animation.rotations.iter() {
+        for (idx, q) in rotations.iter().enumerate() {
+            let mut euler = skeleton::to_euler(skeleton::normalize(*q), order);
+            if let Some(previous) = angles[idx].last() {
+                euler = skeleton::unroll_euler(previous.map(|x| x.to_radians()), euler, order);
+            }
+            angles[idx].push(euler.map(|x| x.to_degrees()));
+        }
+    }
+
+    // depth first like the hierarchy, maya pastes curves onto the selected hierarchy in this order
+    let mut stack: Vec<(usize, usize)> = (0..bones.len()).rev().filter(|x| bones[*x].parent.is_none()).map(|x| (x, 0)).collect();
+    while let Some((idx, depth)) = stack.pop() {
+        let name = bones[idx].name.replace('.', "_");
+        let children = animation.skeleton.children(idx);
+        // curves as attribute, output type and values, the attribute index counts the curves of the node
+        let mut curves: Vec<(String, &str, Vec<f32>)> = Vec::new();
+        if bones[idx].parent.is_none() {
+            for (axis, label) in ["X", "Y", "Z"].iter().enumerate() {
+                let values: Vec<f32> = animation.root_positions.iter().map(|x| [x.x, x.y, x.z][axis] * scale).collect();
+                curves.push((format!("translate.translate{} translate{}", label, label), "linear", values));
+            }
+        }
+        for (axis, label) in ["X", "Y", "Z"].iter().enumerate() {
+            let values: Vec<f32> = angles[idx].iter().map(|x| x[axis]).collect();
+            curves.push((format!("rotate.rotate{} rotate{}", label, label), "angular", values));
+        }
+        for (index, (attribute, output, values)) in curves.iter().enumerate() {
+            writeln!(text, "anim {} {} {} {} {};", attribute, name, depth, children.len(), index).unwrap();
+            write_curve(&mut text, output, values, tolerance);
+        }
+        for child in children.iter().rev() {
+            stack.push((*child, depth + 1));
+        }
+    }
+    return text;
+}
+
+fn write_curve(text: &mut String, output: &str, values: &[f32], tolerance: Option<f32>) {
+    writeln!(text, "animData {{\n  input time;\n  output {};\n  weighted 0;", output).unwrap();
+    text.push_str("  preInfinity constant;\n  postInfinity constant;\n  keys {\n");
+    let keys = match tolerance {
+        Some(tolerance) => reduce_keys(values, tolerance),
+        None => (0..values.len()).collect(),
+    };
+    for frame in keys {
+        writeln!(text, "    {} {} linear linear 1 1 0;", frame, values[frame]).unwrap();
+    }
+    text.push_str("  }\n}\n");
+}
+
+/// Indices of the keys to keep, skipped keys differ at most by tolerance from the line between the kept neighbours.
+pub fn reduce_keys(values: &[f32], tolerance: f32) -> Vec<usize> {
+    if values.len() < 3 {
+        return (0..values.len()).collect();
+    }
+    let mut keys: Vec<usize> = vec![0];
+    let mut start = 0;
+    for end in 2..values.len() {
+        let fits = (start + 1..end).all(|i| {
+            let t = (i - start) as f32 / (end - start) as f32;
+            let interpolated = values[start] + (values[end] - values[start]) * t;
+            (interpolated - values[i]).abs() <= tolerance
+        });
+        if !fits {
+            start = end - 1;
+            keys.push(start);
+        }
+    }
+    keys.push(values.len() - 1);
+    return keys;
+}
+
+/// Maya time unit name of the frame rate.
+fn time_unit(fps: f32) -> String {
+    let unit = match (fps * 1000.0).round() as u32 {
+        15000 => "game",
+        24000 => "film",
+        25000 => "pal",
+        30000 => "ntsc",
+        48000 => "show",
+        50000 => "palf",
+        60000 => "ntscf",
+        _ => return format!("{}fps", fps),
+    };
+    return String::from(unit);
+}
+
+#[cfg(test)]
+mod test {
+    use crate::skeleton::{self, RotationOrder};
+    #[test]
+    fn impl_test() {
+        let frames = vec![skeleton::Frame::default(); 4];
+        let animation = skeleton::animate(&frames, 24.0, false);
+        let text = super::write(&animation, RotationOrder::ZYX, 1.0, None);
+        assert!(text.contains("timeUnit film;\n"));
+        assert!(text.contains("endTime 3;\n"));
+        assert!(text.contains("anim translate.translateX translateX hips 0 3 0;\n"));
+        assert!(text.contains("anim rotate.rotateZ rotateZ forearm_L 3 1 2;\n"));
+        assert_eq!(text.matches("anim ").count(), 3 + 14 * 3);
+        assert_eq!(text.matches("linear linear").count(), (3 + 14 * 3) * 4);
+
+        // constant curves keep the first and the last key
+        let text = super::write(&animation, RotationOrder::ZYX, 1.0, Some(0.01));
+        assert_eq!(text.matches("linear linear").count(), (3 + 14 * 3) * 2);
+
+        let values = [0.0, 1.0, 2.0, 3.0, 2.0, 1.0, 1.05, 1.0];
+        assert_eq!(super::reduce_keys(&values, 0.1), vec![0, 3, 5, 7]);
+        assert_eq!(super::reduce_keys(&values, 0.01), vec![0, 3, 5, 6, 7]);
+        assert_eq!(super::time_unit(120.0), "120fps");
+    }
+}
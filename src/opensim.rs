@@ -0,0 +1,246 @@
+extern crate cgt_math;
+use cgt_math::{Quaternion, Vector3};
+use crate::skeleton::{self, rotate, Animation, RotationOrder};
+use std::fmt::Write;
+
+/// Names of the MediaPipe pose landmarks used as marker names.
+pub const MARKER_NAMES: [&str; 33] = [
+    "nose", "left_eye_inner", "left_eye", "left_eye_outer", "right_eye_inner", "right_eye", "right_eye_outer",
+    "left_ear", "right_ear", "mouth_left", "mouth_right", "left_shoulder", "right_shoulder", "left_elbow", "right_elbow",
+    "left_wrist", "right_wrist", "left_pinky", "right_pinky", "left_index", "right_index", "left_thumb", "right_thumb",
+    "left_hip", "right_hip", "left_knee", "right_knee", "left_ankle", "right_ankle", "left_heel", "right_heel",
+    "left_foot_index", "right_foot_index",
+];
+
+/// Coordinate names of the OpenSim gait models (Rajagopal 2015, gait2392) which can be derived from the pose landmarks.
+pub const COORDINATES: [&str; 25] = [
+    "pelvis_tilt", "pelvis_list", "pelvis_rotation", "pelvis_tx", "pelvis_ty", "pelvis_tz",
+    "hip_flexion_r", "hip_adduction_r", "hip_rotation_r", "knee_angle_r", "ankle_angle_r",
+    "hip_flexion_l", "hip_adduction_l", "hip_rotation_l", "knee_angle_l", "ankle_angle_l",
+    "lumbar_rotation",
+    "arm_flex_r", "arm_add_r", "arm_rot_r", "elbow_flex_r",
+    "arm_flex_l", "arm_add_l", "arm_rot_l", "elbow_flex_l",
+];
+
+/// Centered moving average over `window` frames, frames without detection are skipped and stay empty.
+/// Non-finite landmarks are left out of the average, a landmark without finite neighbours stays unchanged.
+pub fn smooth(frames: &[Vec<[f32; 3]>], window: usize) -> Vec<Vec<[f32; 3]>> {
+    let half = window / 2;
+    let mut result: Vec<Vec<[f32; 3]>> = Vec::with_capacity(frames.len());
+    for (i, frame) in frames.iter().enumerate() {
+        let neighbours: Vec<&Vec<[f32; 3]>> = frames[i.saturating_sub(half)..(i + half + 1).min(frames.len())].iter()
+            .filter(|x| x.len() == frame.len())
+            .collect();
+        let mut smoothed = frame.clone();
+        for (idx, position) in smoothed.iter_mut().enumerate() {
+            let finite: Vec<[f32; 3]> = neighbours.iter()
+                .map(|x| x[idx])
+                .filter(|x| x.iter().all(|v| v.is_finite()))
+                .collect();
+            if finite.is_empty() {
+                continue;
+            }
+            *position = [0.0; 3];
+            for neighbour in finite.iter() {
+                for axis in 0..3 {
+                    position[axis] += neighbour[axis] / finite.len() as f32;
+                }
+            }
+        }
+        result.push(smoothed);
+    }
+    return result;
+}
+
+/// Converts MediaPipe world coordinates to OpenSim's ground frame, x forward, y up and z to the right.
+pub fn to_opensim_space(v: [f32; 3]) -> [f32; 3] {
+    return [-v[2], -v[1], -v[0]];
+}
+
+/// Writes the pose landmarks as OpenSim .trc marker file in meters, frames without detection have empty cells.
+pub fn write_trc(frames: &[Vec<[f32; 3]>], fps: f32, name: &str) -> String {
+    let mut text = format!("PathFileType\t4\t(X/Y/Z)\t{}\n", name);
+    text.push_str("DataRate\tCameraRate\tNumFrames\tNumMarkers\tUnits\tOrigDataRate\tOrigDataStartFrame\tOrigNumFrames\n");
+    writeln!(text, "{}\t{}\t{}\t{}\tm\t{}\t1\t{}", fps, fps, frames.len(), MARKER_NAMES.len(), fps, frames.len()).unwrap();
+
+    let markers: Vec<String> = MARKER_NAMES.iter().map(|x| format!("{}\t\t\t", x)).collect();
+    writeln!(text, "Frame#\tTime\t{}", markers.concat().trim_end()).unwrap();
+    let axes: Vec<String> = (1..=MARKER_NAMES.len()).map(|i| format!("X{}\tY{}\tZ{}", i, i, i)).collect();
+    writeln!(text, "\t\t{}\n", axes.join("\t")).unwrap();
+
+    for (i, frame) in frames.iter().enumerate() {
+        let mut values: Vec<String> = vec![(i + 1).to_string(), format!("{:.6}", i as f32 / fps)];
+        for idx in 0..MARKER_NAMES.len() {
+            match frame.get(idx) {
+                Some(position) if position.iter().all(|x| x.is_finite()) => {
+                    values.extend(to_opensim_space(*position).iter().map(|x| format!("{:.6}", x)));
+                },
+                _ => values.extend(["", "", ""].map(String::from)),
+            }
+        }
+        writeln!(text, "{}", values.join("\t")).unwrap();
+    }
+    return text;
+}
+
+/// Joint angles in degrees and the pelvis translation in meters per frame, in the order of `COORDINATES`.
+/// Angles follow the signs of the Rajagopal model, the pelvis angles are body fixed Z-X-Y rotations.
+/// The pelvis and chest share the spine direction, so the lumbar joint only rotates.
+/// Hip and shoulder flexion and adduction follow the thigh and upper arm direction in the pelvis and chest frame.
+/// The hip rotation follows the bent shank or the foot, the arm rotation the bent forearm and keeps its value while the arm is straight.
+/// Knees and elbows are positive in flexion and the arm angles are relative to arms hanging at the sides.
+pub fn coordinates(animation: &Animation) -> Vec<[f32; 25]> {
+    let skeleton = &animation.skeleton;
+    let index = |name: &str| skeleton.index(name);
+    let names = ["hips", "chest", "thigh.R", "shin.R", "foot.R", "thigh.L", "shin.L", "foot.L",
+        "upper_arm.R", "forearm.R", "hand.R", "upper_arm.L", "forearm.L", "hand.L"];
+    let bones: Option<Vec<usize>> = names.iter().map(|x| index(x)).collect();
+    let bones = match bones {
+        Some(bones) => bones,
+        None => return vec![[0.0; 25]; animation.frame_count()],
+    };
+
+    let mut result: Vec<[f32; 25]> = Vec::with_capacity(animation.frame_count());
+    let mut values = [0.0f32; 25];
+    for frame in 0..animation.frame_count() {
+        let (positions, rotations) = animation.world_transforms(frame);
+        let toe = |foot: usize| positions[foot] + rotate(rotations[foot], skeleton.bones[foot].end.unwrap_or(Vector3::Z));
+
+        let [x, y, z] = angles(rotations[bones[0]]);
+        let root = skeleton_to_opensim(animation.root_positions[frame]);
+        let [tx, ty, tz] = [root.x, root.y, root.z];
+        values[0..6].copy_from_slice(&[z, x, y, tx, ty, tz]);
+
+        for (side, offset) in [(0, 6), (1, 11)] {
+            let [hip, knee, ankle] = [2, 3, 4].map(|x| positions[bones[x + side * 3]]);
+            let toe = toe(bones[4 + side * 3]);
+            let to_pelvis = |v: Vector3| in_frame(rotations[bones[0]], v, side == 1);
+            let (thigh, shank, foot) = (to_pelvis(knee - hip), to_pelvis(ankle - knee), to_pelvis(toe - ankle));
+            let knee_angle = thigh.angle(shank).to_degrees();
+            // internal rotation swings the bent shank to the side, the straight leg uses the foot
+            let reference = if knee_angle > 30.0 { shank.neg() } else { foot };
+            let [flexion, adduction, rotation] = limb_angles(thigh, Some(reference), values[offset + 2]);
+            values[offset..offset + 5].copy_from_slice(&[flexion, adduction, rotation, knee_angle, 90.0 - shank.neg().angle(foot).to_degrees()]);
+        }
+
+        values[16] = angles(skeleton::conjugate(rotations[bones[0]]) * rotations[bones[1]])[1];
+
+        for (side, offset) in [(0, 17), (1, 21)] {
+            let [shoulder, elbow, wrist] = [8, 9, 10].map(|x| positions[bones[x + side * 3]]);
+            let to_chest = |v: Vector3| in_frame(rotations[bones[1]], v, side == 1);
+            let (upper_arm, forearm) = (to_chest(elbow - shoulder), to_chest(wrist - elbow));
+            let elbow_angle = upper_arm.angle(forearm).to_degrees();
+            // internal rotation swings the bent forearm to the body
+            let reference = if elbow_angle > 30.0 { Some(forearm) } else { None };
+            let [flexion, adduction, rotation] = limb_angles(upper_arm, reference, values[offset + 2]);
+            values[offset..offset + 4].copy_from_slice(&[flexion, adduction, rotation, elbow_angle]);
+        }
+        result.push(values);
+    }
+    return result;
+}
+
+/// Writes the joint angles as OpenSim .mot motion file.
+pub fn write_mot(animation: &Animation, name: &str) -> String {
+    let coordinates = coordinates(animation);
+    let mut text = format!("{}\nversion=1\nnRows={}\nnColumns={}\ninDegrees=yes\nendheader\n", name, coordinates.len(), COORDINATES.len() + 1);
+    writeln!(text, "time\t{}", COORDINATES.join("\t")).unwrap();
+    for (i, values) in coordinates.iter().enumerate() {
+        let values: Vec<String> = values.iter().map(|x| format!("{:.6}", x)).collect();
+        writeln!(text, "{:.6}\t{}", i as f32 * animation.frame_time, values.join("\t")).unwrap();
+    }
+    return text;
+}
+
+fn skeleton_to_opensim(v: Vector3) -> Vector3 {
+    return Vector3::new(v.z, v.y, -v.x);
+}
+
+/// Direction in the OpenSim axes of a pelvis or chest frame, the left side is mirrored at the sagittal plane.
+fn in_frame(frame: Quaternion, v: Vector3, mirror: bool) -> Vector3 {
+    let local = skeleton_to_opensim(rotate(skeleton::conjugate(frame), v));
+    return if mirror { Vector3::new(local.x, local.y, -local.z) } else { local };
+}
+
+/// Flexion about z and adduction about x of a segment hanging down, the rotation about its axis turns the
+/// reference direction, which points forward at zero rotation, to the medial side.
+fn limb_angles(segment: Vector3, reference: Option<Vector3>, previous: f32) -> [f32; 3] {
+    let d = segment.normalize();
+    // the flexion is undefined for segments pointing to the side
+    let flexion = if d.x.abs() + d.y.abs() > 1e-6 { d.x.atan2(-d.y) } else { 0.0 };
+    let adduction = (-d.z).clamp(-1.0, 1.0).asin();
+    let rotation = match reference {
+        Some(reference) => {
+            // undo flexion and adduction, the segment hangs down in the local frame
+            let q = Quaternion::from_rotation_z(flexion) * Quaternion::from_rotation_x(adduction);
+            let r = rotate(skeleton::conjugate(q), reference);
+            (-r.z).atan2(r.x).to_degrees()
+        },
+        None => previous,
+    };
+    return [flexion.to_degrees(), adduction.to_degrees(), rotation];
+}
+
+/// Z-X-Y euler angles in degrees of a skeleton rotation in OpenSim axes.
+fn angles(q: Quaternion) -> [f32; 3] {
+    // skeleton axes (left, up, forward) to OpenSim axes (forward, up, right) are a rotation about y
+    let basis = Quaternion::from_rotation_y(90f32.to_radians());
+    let q = basis * skeleton::normalize(q) * skeleton::conjugate(basis);
+    return skeleton::to_euler(q, RotationOrder::ZXY).map(|x| x.to_degrees());
+}
+
+#[cfg(test)]
+mod test {
+    use cgt_math::Quaternion;
+    use crate::skeleton;
+    #[test]
+    fn impl_test() {
+        let frames = vec![vec![[0.0, 0.0, 0.0]; 33], Vec::new(), vec![[1.0, 2.0, 3.0]; 33]];
+        let smoothed = super::smooth(&frames, 3);
+        assert_eq!(smoothed[0][0], [0.0, 0.0, 0.0]);
+        assert!(smoothed[1].is_empty());
+        assert_eq!(smoothed[2][5], [1.0, 2.0, 3.0]);
+        assert_eq!(super::smooth(&frames[0..1], 5)[0].len(), 33);
+
+        // a missing landmark neither spreads to its neighbours nor keeps them from smoothing
+        let mut gaps = vec![vec![[0.0, 0.0, 0.0]; 33], vec![[2.0, 2.0, 2.0]; 33], vec![[4.0, 4.0, 4.0]; 33]];
+        gaps[1][3] = [f32::NAN; 3];
+        let smoothed = super::smooth(&gaps, 3);
+        assert_eq!(smoothed[0][3], [0.0, 0.0, 0.0]);
+        assert_eq!(smoothed[1][3], [2.0, 2.0, 2.0]);
+        assert_eq!(smoothed[1][4], [2.0, 2.0, 2.0]);
+        assert_eq!(smoothed[2][3], [4.0, 4.0, 4.0]);
+        let missing = super::smooth(&[vec![[f32::NAN; 3]; 33]], 3);
+        assert!(missing[0][3][0].is_nan());
+
+        let text = super::write_trc(&frames, 30.0, "take.trc");
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines[0], "PathFileType\t4\t(X/Y/Z)\ttake.trc");
+        assert_eq!(lines[2], "30\t30\t3\t33\tm\t30\t1\t3");
+        assert!(lines[3].starts_with("Frame#\tTime\tnose\t\t\tleft_eye_inner"));
+        assert!(lines[4].ends_with("X33\tY33\tZ33"));
+        assert_eq!(lines[6].split('\t').count(), 2 + 33 * 3);
+        assert_eq!(lines[8].split('\t').take(5).collect::<Vec<&str>>(), ["3", "0.066667", "-3.000000", "-2.000000", "-1.000000"]);
+
+        // the rest pose has zero angles except for the abducted arms
+        let animation = skeleton::animate(&vec![skeleton::Frame::default(); 2], 30.0, false);
+        let values = super::coordinates(&animation);
+        for (i, name) in super::COORDINATES.iter().enumerate() {
+            let expected = if name.starts_with("arm_add") { -90.0 } else { 0.0 };
+            assert!((values[1][i] - expected).abs() < 0.05, "{} {}", name, values[1][i]);
+        }
+
+        // flexed right hip and left knee
+        let mut animation = animation;
+        let thigh = animation.skeleton.index("thigh.R").unwrap();
+        let shin = animation.skeleton.index("shin.L").unwrap();
+        animation.rotations[0][thigh] = Quaternion::from_rotation_x(-0.5);
+        animation.rotations[0][shin] = Quaternion::from_rotation_x(0.5);
+        let values = super::coordinates(&animation);
+        assert!((values[0][6] - 0.5f32.to_degrees()).abs() < 1e-3);
+        assert!((values[0][14] - 0.5f32.to_degrees()).abs() < 1e-3);
+
+        let text = super::write_mot(&animation, "take.mot");
+        assert!(text.starts_with("take.mot\nversion=1\nnRows=2\nnColumns=26\ninDegrees=yes\nendheader\ntime\tpelvis_tilt\t"));
+    }
+}
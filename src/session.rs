@@ -0,0 +1,240 @@
+use crate::skeleton::Frame;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufWriter, Seek, SeekFrom, Write};
+use std::path::Path;
+
+/// Session files start with the magic and the format version, followed by the frames until the end of the file.
+/// A frame is a f64 timestamp in seconds and a u8 mask of the present blocks (pose, face, left hand, right hand),
+/// every present block is a u16 landmark count and x, y, z and visibility as f32 per landmark, all little endian.
+pub const MAGIC: &[u8; 4] = b"MPRS";
+pub const VERSION: u16 = 1;
+const HEADER_SIZE: usize = 8;
+
+/// Detected landmarks with their visibility.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Detection {
+    pub landmarks: Vec<[f32; 3]>,
+    pub visibility: Vec<f32>,
+}
+
+/// Recorded frame, blocks without detection are None.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SessionFrame {
+    pub timestamp: f64,
+    pub pose: Option<Detection>,
+    pub face: Option<Detection>,
+    pub left_hand: Option<Detection>,
+    pub right_hand: Option<Detection>,
+}
+
+impl SessionFrame {
+    /// Landmarks for the solvers, missing blocks are empty.
+    pub fn to_frame(&self) -> Frame {
+        let landmarks = |x: &Option<Detection>| x.as_ref().map(|x| x.landmarks.clone()).unwrap_or_default();
+        return Frame {
+            pose: landmarks(&self.pose),
+            left_hand: landmarks(&self.left_hand),
+            right_hand: landmarks(&self.right_hand),
+            face: landmarks(&self.face),
+        };
+    }
+
+    fn blocks(&self) -> [&Option<Detection>; 4] {
+        return [&self.pose, &self.face, &self.left_hand, &self.right_hand];
+    }
+}
+
+/// Appends frames to a session file while recording.
+pub struct SessionWriter {
+    file: BufWriter<File>,
+}
+
+impl SessionWriter {
+    /// Opens a session for appending, a new file gets the header.
+    /// A truncated last frame of an interrupted recording is removed before appending.
+    pub fn open(path: &Path) -> io::Result<SessionWriter> {
+        let mut file = OpenOptions::new().read(true).write(true).create(true).truncate(false).open(path)?;
+        if file.metadata()?.len() == 0 {
+            write_header(&mut file)?;
+        }
+        else {
+            let data = fs::read(path)?;
+            let (_, length) = decode_frames(&data).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+            file.set_len(length as u64)?;
+        }
+        file.seek(SeekFrom::End(0))?;
+        return Ok(SessionWriter { file: BufWriter::new(file) });
+    }
+
+    pub fn write(&mut self, frame: &SessionFrame) -> io::Result<()> {
+        return write_frame(&mut self.file, frame);
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        return self.file.flush();
+    }
+}
+
+pub fn write_header(out: &mut impl Write) -> io::Result<()> {
+    out.write_all(MAGIC)?;
+    out.write_all(&VERSION.to_le_bytes())?;
+    return out.write_all(&0u16.to_le_bytes());
+}
+
+/// Writes a frame, missing visibility values are written as 1.
+pub fn write_frame(out: &mut impl Write, frame: &SessionFrame) -> io::Result<()> {
+    let mut data: Vec<u8> = Vec::new();
+    data.extend_from_slice(&frame.timestamp.to_le_bytes());
+    let mask = frame.blocks().iter().enumerate().filter(|(_, x)| x.is_some()).fold(0u8, |mask, (i, _)| mask | 1 << i);
+    data.push(mask);
+    for detection in frame.blocks().iter().filter_map(|x| x.as_ref()) {
+        let count = detection.landmarks.len().min(u16::MAX as usize);
+        data.extend_from_slice(&(count as u16).to_le_bytes());
+        for (i, position) in detection.landmarks.iter().take(count).enumerate() {
+            let visibility = detection.visibility.get(i).copied().unwrap_or(1.0);
+            for value in [position[0], position[1], position[2], visibility] {
+                data.extend_from_slice(&value.to_le_bytes());
+            }
+        }
+    }
+    return out.write_all(&data);
+}
+
+/// Encodes a complete session.
+pub fn encode(frames: &[SessionFrame]) -> Vec<u8> {
+    let mut data: Vec<u8> = Vec::new();
+    write_header(&mut data).unwrap();
+    for frame in frames.iter() {
+        write_frame(&mut data, frame).unwrap();
+    }
+    return data;
+}
+
+/// Decodes a session, a truncated last frame of an interrupted recording is skipped.
+pub fn decode(data: &[u8]) -> Result<Vec<SessionFrame>, String> {
+    return decode_frames(data).map(|(frames, _)| frames);
+}
+
+pub fn load(path: &Path) -> Result<Vec<SessionFrame>, String> {
+    let data = fs::read(path).map_err(|err| format!("Failed to read {}: {}", path.display(), err))?;
+    return decode(&data);
+}
+
+pub fn save(path: &Path, frames: &[SessionFrame]) -> io::Result<()> {
+    return fs::write(path, encode(frames));
+}
+
+/// Mean frame rate of the timestamps.
+pub fn frame_rate(frames: &[SessionFrame]) -> Option<f32> {
+    let (first, last) = (frames.first()?, frames.last()?);
+    let duration = last.timestamp - first.timestamp;
+    if frames.len() < 2 || duration <= 0.0 {
+        return None;
+    }
+    return Some(((frames.len() - 1) as f64 / duration) as f32);
+}
+
+/// Decoded frames and the byte length of the complete frames including the header.
+fn decode_frames(data: &[u8]) -> Result<(Vec<SessionFrame>, usize), String> {
+    if data.len() < HEADER_SIZE || &data[0..4] != MAGIC {
+        return Err(String::from("Expected a session file."));
+    }
+    let version = u16::from_le_bytes([data[4], data[5]]);
+    if version != VERSION {
+        return Err(format!("Unsupported session version {}.", version));
+    }
+
+    let mut frames: Vec<SessionFrame> = Vec::new();
+    let mut position = HEADER_SIZE;
+    while let Some((frame, length)) = decode_frame(&data[position..])? {
+        frames.push(frame);
+        position += length;
+    }
+    return Ok((frames, position));
+}
+
+/// Frame and its byte length, None if the data ends within the frame.
+fn decode_frame(data: &[u8]) -> Result<Option<(SessionFrame, usize)>, String> {
+    if data.len() < 9 {
+        return Ok(None);
+    }
+    let mut timestamp = [0u8; 8];
+    timestamp.copy_from_slice(&data[0..8]);
+    let mut frame = SessionFrame { timestamp: f64::from_le_bytes(timestamp), ..SessionFrame::default() };
+    let mask = data[8];
+    if mask > 0x0F {
+        return Err(format!("Invalid block mask {} at {}s.", mask, frame.timestamp));
+    }
+
+    let mut position = 9;
+    let float = |i: usize| f32::from_le_bytes([data[i], data[i + 1], data[i + 2], data[i + 3]]);
+    let mut blocks: [Option<Detection>; 4] = Default::default();
+    for (i, block) in blocks.iter_mut().enumerate() {
+        if mask & 1 << i == 0 {
+            continue;
+        }
+        if data.len() < position + 2 {
+            return Ok(None);
+        }
+        let count = u16::from_le_bytes([data[position], data[position + 1]]) as usize;
+        position += 2;
+        if data.len() < position + count * 16 {
+            return Ok(None);
+        }
+        let mut detection = Detection { landmarks: Vec::with_capacity(count), visibility: Vec::with_capacity(count) };
+        for _ in 0..count {
+            detection.landmarks.push([float(position), float(position + 4), float(position + 8)]);
+            detection.visibility.push(float(position + 12));
+            position += 16;
+        }
+        *block = Some(detection);
+    }
+    let [pose, face, left_hand, right_hand] = blocks;
+    frame.pose = pose;
+    frame.face = face;
+    frame.left_hand = left_hand;
+    frame.right_hand = right_hand;
+    return Ok(Some((frame, position)));
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Detection, SessionFrame};
+    #[test]
+    fn impl_test() {
+        let pose = Detection { landmarks: vec![[0.1, 0.2, 0.3]; 33], visibility: vec![0.5; 33] };
+        let hand = Detection { landmarks: vec![[1.0, 2.0, 3.0]; 21], visibility: Vec::new() };
+        let frames = vec![
+            SessionFrame { timestamp: 0.0, pose: Some(pose.clone()), ..SessionFrame::default() },
+            SessionFrame { timestamp: 0.5, ..SessionFrame::default() },
+            SessionFrame { timestamp: 1.0, pose: Some(pose), right_hand: Some(hand), ..SessionFrame::default() },
+        ];
+        let data = super::encode(&frames);
+        assert_eq!(&data[0..4], b"MPRS");
+        assert_eq!(data.len(), 8 + 3 * 9 + 2 * (2 + 33 * 16) + 2 + 21 * 16);
+
+        let decoded = super::decode(&data).unwrap();
+        assert_eq!(decoded[..2], frames[..2]);
+        // missing visibility is written as visible
+        assert_eq!(decoded[2].right_hand.as_ref().unwrap().visibility, vec![1.0; 21]);
+        assert_eq!(decoded[2].to_frame().right_hand.len(), 21);
+        assert!(decoded[1].to_frame().pose.is_empty());
+        assert_eq!(super::frame_rate(&decoded), Some(2.0));
+
+        // a truncated frame is skipped, other data is rejected
+        assert_eq!(super::decode(&data[..data.len() - 5]).unwrap().len(), 2);
+        assert!(super::decode(b"MPRS\x02\x00\x00\x00").is_err());
+        assert!(super::decode(b"{}").is_err());
+
+        // streaming append continues after the last complete frame
+        let path = std::env::temp_dir().join(format!("mpr_session_{}.mprs", std::process::id()));
+        std::fs::write(&path, &data[..data.len() - 5]).unwrap();
+        let mut writer = super::SessionWriter::open(&path).unwrap();
+        writer.write(&frames[1]).unwrap();
+        writer.flush().unwrap();
+        let loaded = super::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.len(), 3);
+        assert_eq!(loaded[2], frames[1]);
+    }
+}
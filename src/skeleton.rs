@@ -0,0 +1,590 @@
+extern crate cgt_math;
+use cgt_math::{Quaternion, Vector3};
+use crate::{face, hand, pose};
+
+// Quaternions are serialized as [x, y, z, w] like Quaternion::to_array.
+// The skeleton is Y-up, the character faces +Z and its left side points to +X.
+// The rest pose is a T-pose with palms facing downwards.
+
+const S: f32 = std::f32::consts::FRAC_1_SQRT_2;
+// MediaPipe space (x right, y down, z away from the camera) to skeleton space, 180 degrees around x.
+const TO_SKELETON: Quaternion = Quaternion { x: 1.0, y: 0.0, z: 0.0, w: 0.0 };
+// Solver rotations of the bones in rest pose, the solver tracks limbs with their x-axis.
+const REST_LEFT: Quaternion = Quaternion::IDENTITY;
+const REST_RIGHT: Quaternion = Quaternion { x: 0.0, y: 1.0, z: 0.0, w: 0.0 };
+const REST_DOWN: Quaternion = Quaternion { x: 0.0, y: 0.0, z: -S, w: S };
+
+pub const FINGER_NAMES: [&str; 5] = ["thumb", "index", "middle", "ring", "pinky"];
+
+/// Pose bones as name, parent name, solver rotation index and landmarks of head and tail.
+/// The hips and chest are placed at the hip and shoulder center.
+pub const POSE_BONES: [(&str, &str, usize, [usize; 2]); 14] = [
+    ("hips", "", 33, [33, 34]),
+    ("chest", "hips", 34, [34, 34]),
+    ("upper_arm.L", "chest", 11, [11, 13]),
+    ("forearm.L", "upper_arm.L", 13, [13, 15]),
+    ("hand.L", "forearm.L", 15, [15, 19]),
+    ("upper_arm.R", "chest", 12, [12, 14]),
+    ("forearm.R", "upper_arm.R", 14, [14, 16]),
+    ("hand.R", "forearm.R", 16, [16, 20]),
+    ("thigh.L", "hips", 23, [23, 25]),
+    ("shin.L", "thigh.L", 25, [25, 27]),
+    ("foot.L", "shin.L", 27, [27, 31]),
+    ("thigh.R", "hips", 24, [24, 26]),
+    ("shin.R", "thigh.R", 26, [26, 28]),
+    ("foot.R", "shin.R", 28, [28, 32]),
+];
+
+/// Names of the hand solver rotations, exporters append the side as .L or .R.
+pub const HAND_BONES: [(usize, &str); 16] = [
+    (0, "hand"),
+    (1, "thumb_01"), (2, "thumb_02"), (3, "thumb_03"),
+    (5, "index_01"), (6, "index_02"), (7, "index_03"),
+    (9, "middle_01"), (10, "middle_02"), (11, "middle_03"),
+    (13, "ring_01"), (14, "ring_02"), (15, "ring_03"),
+    (17, "pinky_01"), (18, "pinky_02"), (19, "pinky_03"),
+];
+
+/// Names of the face solver rotations.
+pub const FACE_BONES: [(usize, &str); 6] = [
+    (0, "head"), (1, "chin"), (2, "mouth_corner.L"), (3, "mouth_corner.R"), (4, "eye.L"), (5, "eye.R"),
+];
+
+/// Face joints below the head as name, offset and tail, relative to the distance between the ears.
+const FACE_JOINTS: [(&str, [f32; 3], [f32; 3]); 5] = [
+    ("chin", [0.0, -0.15, 0.1], [0.0, -0.3, 0.35]),
+    ("mouth_corner.L", [0.2, -0.35, 0.45], [0.05, 0.0, 0.0]),
+    ("mouth_corner.R", [-0.2, -0.35, 0.45], [-0.05, 0.0, 0.0]),
+    ("eye.L", [0.2, 0.1, 0.4], [0.0, 0.0, 0.1]),
+    ("eye.R", [-0.2, 0.1, 0.4], [0.0, 0.0, 0.1]),
+];
+
+/// Euler rotation orders, the rotation matrix is composed in the listed order, e.g. XYZ = X * Y * Z.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RotationOrder {
+    XYZ,
+    XZY,
+    YXZ,
+    YZX,
+    ZXY,
+    ZYX,
+}
+
+impl RotationOrder {
+    pub fn axes(&self) -> [usize; 3] {
+        match self {
+            RotationOrder::XYZ => [0, 1, 2],
+            RotationOrder::XZY => [0, 2, 1],
+            RotationOrder::YXZ => [1, 0, 2],
+            RotationOrder::YZX => [1, 2, 0],
+            RotationOrder::ZXY => [2, 0, 1],
+            RotationOrder::ZYX => [2, 1, 0],
+        }
+    }
+}
+
+impl std::str::FromStr for RotationOrder {
+    type Err = String;
+
+    fn from_str(order: &str) -> Result<Self, Self::Err> {
+        match order.to_uppercase().as_str() {
+            "XYZ" => Ok(RotationOrder::XYZ),
+            "XZY" => Ok(RotationOrder::XZY),
+            "YXZ" => Ok(RotationOrder::YXZ),
+            "YZX" => Ok(RotationOrder::YZX),
+            "ZXY" => Ok(RotationOrder::ZXY),
+            "ZYX" => Ok(RotationOrder::ZYX),
+            _ => Err(format!("Invalid rotation order {}.", order)),
+        }
+    }
+}
+
+/// Landmarks of a single frame, empty if not detected.
+#[derive(Clone, Debug, Default)]
+pub struct Frame {
+    pub pose: Vec<[f32; 3]>,
+    pub left_hand: Vec<[f32; 3]>,
+    pub right_hand: Vec<[f32; 3]>,
+    pub face: Vec<[f32; 3]>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Bone {
+    pub name: String,
+    pub parent: Option<usize>,
+    /// Rest position relative to the parent.
+    pub offset: Vector3,
+    /// Tail relative to the bone head for bones without children.
+    pub end: Option<Vector3>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Skeleton {
+    pub bones: Vec<Bone>,
+}
+
+impl Skeleton {
+    pub fn index(&self, name: &str) -> Option<usize> {
+        return self.bones.iter().position(|x| x.name == name);
+    }
+
+    pub fn children(&self, idx: usize) -> Vec<usize> {
+        return (0..self.bones.len()).filter(|i| self.bones[*i].parent == Some(idx)).collect();
+    }
+}
+
+/// World positions of the bone heads and world rotations of the bones of a frame.
+pub type WorldTransforms = (Vec<Vector3>, Vec<Quaternion>);
+
+/// Baked skeletal animation with local bone rotations per frame.
+#[derive(Clone, Debug)]
+pub struct Animation {
+    pub skeleton: Skeleton,
+    pub frame_time: f32,
+    /// Root position per frame.
+    pub root_positions: Vec<Vector3>,
+    /// Local rotation per frame and bone.
+    pub rotations: Vec<Vec<Quaternion>>,
+}
+
+impl Animation {
+    pub fn frame_count(&self) -> usize {
+        return self.rotations.len();
+    }
+
+    /// World positions of the bone heads and world rotations of the bones, bones are ordered parents first.
+    pub fn world_transforms(&self, frame: usize) -> WorldTransforms {
+        let bones = &self.skeleton.bones;
+        let mut positions = vec![Vector3::ZERO; bones.len()];
+        let mut rotations = vec![Quaternion::IDENTITY; bones.len()];
+        for (i, bone) in bones.iter().enumerate() {
+            let local = self.rotations[frame][i];
+            match bone.parent {
+                Some(p) => {
+                    positions[i] = positions[p] + rotate(rotations[p], bone.offset);
+                    rotations[i] = rotations[p] * local;
+                },
+                None => {
+                    positions[i] = self.root_positions[frame];
+                    rotations[i] = local;
+                },
+            }
+        }
+        return (positions, rotations);
+    }
+}
+
+/// Solves the frames and bakes the solver rotations to a T-pose skeleton.
+/// Bone lengths are averaged over all frames and between the left and right side.
+/// Frames without pose keep the previous pose, fingers are added if `hands` is set.
+/// Head, chin, mouth corner and eye joints are added if any frame contains face landmarks.
+pub fn animate(frames: &[Frame], fps: f32, hands: bool) -> Animation {
+    let skeleton = build_skeleton(frames, hands);
+    let mut root_positions: Vec<Vector3> = Vec::with_capacity(frames.len());
+    let mut rotations: Vec<Vec<Quaternion>> = Vec::with_capacity(frames.len());
+    let mut last_root = Vector3::ZERO;
+    let mut last_rotations = vec![Quaternion::IDENTITY; skeleton.bones.len()];
+    let mut chest = Quaternion::IDENTITY;
+
+    for frame in frames.iter() {
+        if frame.pose.len() == 33 {
+            let hip_center = Vector3::from_array(frame.pose[23]).center(Vector3::from_array(frame.pose[24]));
+            last_root = to_skeleton_space(hip_center);
+            let world = pose_world_rotations(&pose::main(&frame.pose), &frame.pose);
+            for (i, (_, parent, _, _)) in POSE_BONES.iter().enumerate() {
+                let local = match skeleton.index(parent) {
+                    Some(p) => conjugate(world[p]) * world[i],
+                    None => world[i],
+                };
+                last_rotations[i] = finite_or_identity(normalize(local));
+            }
+            chest = world[1];
+        }
+        if let (Some(head), true) = (skeleton.index("head"), frame.face.len() >= 468) {
+            // a single head pose fit drives the head and the face joints
+            let (solved, head_frame) = face::main_with_frame(&frame.face);
+            let local = conjugate(chest) * head_world_rotation(&head_frame);
+            last_rotations[head] = finite_or_identity(normalize(local));
+            for (idx, name) in FACE_BONES.iter().skip(1) {
+                if let Some(joint) = skeleton.index(name) {
+                    last_rotations[joint] = finite_or_identity(from_head_local(solved[*idx]));
+                }
+            }
+        }
+        if hands {
+            for (side, data) in [("L", &frame.left_hand), ("R", &frame.right_hand)] {
+                if data.len() == 21 {
+                    finger_rotations(&skeleton, side, &hand::angles(data), hand::thumb_opposition(data), &mut last_rotations);
+                }
+            }
+        }
+        root_positions.push(last_root);
+        rotations.push(last_rotations.clone());
+    }
+
+    return Animation { skeleton, frame_time: 1.0 / fps, root_positions, rotations };
+}
+
+/// Converts the pose solver rotations to world rotations of the T-pose skeleton.
+fn pose_world_rotations(rotation_data: &[Quaternion; 36], pose: &[[f32; 3]]) -> [Quaternion; 14] {
+    let solved = |idx: usize| TO_SKELETON * normalize(rotation_data[idx]) * conjugate(TO_SKELETON);
+    let rest = |name: &str| {
+        if name.starts_with("thigh") || name.starts_with("shin") { REST_DOWN }
+        else if name.ends_with(".L") { REST_LEFT }
+        else { REST_RIGHT }
+    };
+
+    let mut world = [Quaternion::IDENTITY; 14];
+    // the torso solver axes are not orthonormal, the hips and chest face forward with the spine up
+    let landmark = |idx: usize| to_skeleton_space(Vector3::from_array(pose[idx]));
+    let [hip_center, shoulder_center] = [landmark(23).center(landmark(24)), landmark(11).center(landmark(12))];
+    let spine = shoulder_center - hip_center;
+    for (i, [left, right]) in [(0, [23, 24]), (1, [11, 12])] {
+        let left = landmark(left) - landmark(right);
+        world[i] = look_rotation(left.cross(spine), spine);
+    }
+    for (i, (name, _, idx, _)) in POSE_BONES.iter().enumerate().skip(2) {
+        world[i] = solved(*idx) * conjugate(rest(name));
+    }
+    // the solvers foot axes are not orthogonal, the foot points to the foot index and the knee is up
+    for (i, [knee, ankle, foot_index]) in [(10, [25, 27, 31]), (13, [26, 28, 32])] {
+        let [knee, ankle, foot_index] = [knee, ankle, foot_index].map(landmark);
+        world[i] = look_rotation(foot_index - ankle, knee - ankle);
+    }
+    for q in world.iter_mut() {
+        *q = finite_or_identity(*q);
+    }
+    return world;
+}
+
+/// Head rotation from the head frame fitted to the face landmarks, the head looks out of the face
+/// and the ears are horizontal in rest pose.
+fn head_world_rotation(frame: &face::HeadFrame) -> Quaternion {
+    // the head-local axes are in the space of set_face_origin
+    let to_skeleton = |v: Vector3| Vector3::new(-v.x, v.z, -v.y);
+    let forward = to_skeleton(frame.y);
+    return look_rotation(forward, forward.cross(to_skeleton(frame.x)));
+}
+
+/// Converts a rotation of the face solvers head-local frame (x left, y out of the face, z up) to the skeleton axes.
+/// Swapping y and z mirrors the frame, so the rotation axis gets swapped and flipped.
+fn from_head_local(q: Quaternion) -> Quaternion {
+    let q = normalize(q);
+    return Quaternion { x: -q.x, y: -q.z, z: -q.y, w: q.w };
+}
+
+/// Local finger rotations from the hand angles, flexion bends towards the palm and spread turns around the palm normal.
+/// The thumb opposition twists the first thumb bone around its own axis.
+fn finger_rotations(skeleton: &Skeleton, side: &str, angles: &[[f32; 2]; 21], thumb_twist: f32, rotations: &mut [Quaternion]) {
+    let sign = if side == "L" { -1.0 } else { 1.0 };
+    for (f, finger) in hand::FINGERS.iter().enumerate() {
+        for j in 0..3 {
+            let name = format!("{}_0{}.{}", FINGER_NAMES[f], j + 1, side);
+            let idx = match skeleton.index(&name) {
+                Some(idx) => idx,
+                None => continue,
+            };
+            let [flexion, spread] = angles[finger[j]];
+            let mut q = Quaternion::IDENTITY;
+            if !flexion.is_nan() {
+                q = Quaternion::from_rotation_z(sign * flexion);
+            }
+            if !spread.is_nan() {
+                q *= Quaternion::from_rotation_y(-sign * spread);
+            }
+            if f == 0 && j == 0 && !thumb_twist.is_nan() {
+                q *= Quaternion::from_rotation_x(-thumb_twist);
+            }
+            rotations[idx] = q;
+        }
+    }
+}
+
+/// Builds the T-pose skeleton from the mean bone lengths.
+fn build_skeleton(frames: &[Frame], hands: bool) -> Skeleton {
+    // mean distances between landmarks over all valid frames
+    let pose_frames: Vec<Vec<Vector3>> = frames.iter()
+        .filter(|x| x.pose.len() == 33)
+        .map(|x| x.pose.iter().map(|v| Vector3::from_array(*v)).collect())
+        .collect();
+    let pose_length = |a: usize, b: usize| -> f32 {
+        let dists: Vec<f32> = pose_frames.iter().map(|x| x[a].distance_to(x[b])).filter(|x| x.is_finite()).collect();
+        if dists.is_empty() { 0.1 } else { dists.iter().sum::<f32>() / dists.len() as f32 }
+    };
+    let symmetric = |left: [usize; 2], right: [usize; 2]| -> f32 {
+        (pose_length(left[0], left[1]) + pose_length(right[0], right[1])) / 2.0
+    };
+
+    let hip_width = pose_length(23, 24);
+    let shoulder_width = pose_length(11, 12);
+    let spine = pose_frames.iter()
+        .map(|x| x[23].center(x[24]).distance_to(x[11].center(x[12])))
+        .filter(|x| x.is_finite())
+        .fold((0.0f32, 0usize), |acc, x| (acc.0 + x, acc.1 + 1));
+    let spine = if spine.1 > 0 { spine.0 / spine.1 as f32 } else { 0.5 };
+    let upper_arm = symmetric([11, 13], [12, 14]);
+    let forearm = symmetric([13, 15], [14, 16]);
+    let hand_length = symmetric([15, 19], [16, 20]);
+    let thigh = symmetric([23, 25], [24, 26]);
+    let shin = symmetric([25, 27], [26, 28]);
+    let foot = symmetric([27, 31], [28, 32]);
+
+    let mut bones: Vec<Bone> = Vec::new();
+    let add = |name: &str, parent: &str, offset: Vector3, end: Option<Vector3>, bones: &mut Vec<Bone>| {
+        let parent = bones.iter().position(|x| x.name == parent);
+        bones.push(Bone { name: name.to_string(), parent, offset, end });
+    };
+    let hands_end = if hands { None } else { Some(Vector3::new(hand_length, 0.0, 0.0)) };
+    add("hips", "", Vector3::ZERO, None, &mut bones);
+    add("chest", "hips", Vector3::new(0.0, spine, 0.0), None, &mut bones);
+    for (side, sign) in [("L", 1.0f32), ("R", -1.0f32)] {
+        let end = hands_end.map(|x| x * sign);
+        add(&format!("upper_arm.{}", side), "chest", Vector3::new(sign * shoulder_width / 2.0, 0.0, 0.0), None, &mut bones);
+        add(&format!("forearm.{}", side), &format!("upper_arm.{}", side), Vector3::new(sign * upper_arm, 0.0, 0.0), None, &mut bones);
+        add(&format!("hand.{}", side), &format!("forearm.{}", side), Vector3::new(sign * forearm, 0.0, 0.0), end, &mut bones);
+    }
+    for (side, sign) in [("L", 1.0f32), ("R", -1.0f32)] {
+        add(&format!("thigh.{}", side), "hips", Vector3::new(sign * hip_width / 2.0, 0.0, 0.0), None, &mut bones);
+        add(&format!("shin.{}", side), &format!("thigh.{}", side), Vector3::new(0.0, -thigh, 0.0), None, &mut bones);
+        add(&format!("foot.{}", side), &format!("shin.{}", side), Vector3::new(0.0, -shin, 0.0), Some(Vector3::new(0.0, 0.0, foot)), &mut bones);
+    }
+
+    if hands {
+        for (side, sign) in [("L", 1.0f32), ("R", -1.0f32)] {
+            let layout = finger_layout(frames, side, hand_length);
+            for (f, joints) in layout.iter().enumerate() {
+                let mut parent = format!("hand.{}", side);
+                for j in 0..3 {
+                    let name = format!("{}_0{}.{}", FINGER_NAMES[f], j + 1, side);
+                    let offset = if j == 0 {
+                        Vector3::new(sign * joints[0][0], 0.0, joints[0][1])
+                    } else {
+                        Vector3::new(sign * joints[j][0], 0.0, 0.0)
+                    };
+                    let end = if j == 2 { Some(Vector3::new(sign * joints[3][0], 0.0, 0.0)) } else { None };
+                    add(&name, &parent, offset, end, &mut bones);
+                    parent = name;
+                }
+            }
+        }
+    }
+
+    let face_frames = frames.iter().filter(|x| x.face.len() >= 468).count();
+    if face_frames > 0 {
+        let ear_width = pose_length(7, 8);
+        let neck = pose_frames.iter()
+            .map(|x| x[11].center(x[12]).distance_to(x[7].center(x[8])))
+            .filter(|x| x.is_finite())
+            .fold((0.0f32, 0usize), |acc, x| (acc.0 + x, acc.1 + 1));
+        let neck = if neck.1 > 0 { neck.0 / neck.1 as f32 } else { spine * 0.3 };
+        add("head", "chest", Vector3::new(0.0, neck, 0.0), None, &mut bones);
+        for (name, offset, end) in FACE_JOINTS.iter() {
+            add(name, "head", Vector3::from_array(*offset) * ear_width, Some(Vector3::from_array(*end) * ear_width), &mut bones);
+        }
+    }
+    return Skeleton { bones };
+}
+
+/// Finger rest layout per finger as [along, lateral] of the first joint relative to the wrist
+/// followed by the lengths of the three segments, scaled to the pose hand length.
+fn finger_layout(frames: &[Frame], side: &str, hand_length: f32) -> [[[f32; 2]; 4]; 5] {
+    let mut layout = [[[0.0f32; 2]; 4]; 5];
+    let mut count = 0usize;
+    for frame in frames.iter() {
+        let data = if side == "L" { &frame.left_hand } else { &frame.right_hand };
+        if data.len() != 21 {
+            continue;
+        }
+        let point = |idx: usize| Vector3::from_array(data[idx]);
+        let scale = hand_length / point(0).distance_to(point(5));
+        let forward = (point(9) - point(0)).normalize();
+        let lateral = (point(5) - point(17)).normalize();
+        if !scale.is_finite() || !(forward.length() + lateral.length()).is_finite() {
+            continue;
+        }
+        count += 1;
+        for (f, finger) in hand::FINGERS.iter().enumerate() {
+            let base = point(finger[0]) - point(0);
+            layout[f][0][0] += base.dot(forward) * scale;
+            layout[f][0][1] += base.dot(lateral) * scale;
+            for j in 1..4 {
+                layout[f][j][0] += point(finger[j - 1]).distance_to(point(finger[j])) * scale;
+            }
+        }
+    }
+
+    if count == 0 {
+        // evenly spread default hand
+        for f in 0..5 {
+            let segment = hand_length * 0.3;
+            layout[f] = [[hand_length, (2.0 - f as f32) * hand_length * 0.2], [segment, 0.0], [segment, 0.0], [segment, 0.0]];
+        }
+        return layout;
+    }
+    for finger in layout.iter_mut() {
+        for joint in finger.iter_mut() {
+            joint[0] /= count as f32;
+            joint[1] /= count as f32;
+        }
+    }
+    return layout;
+}
+
+pub fn to_skeleton_space(vec: Vector3) -> Vector3 {
+    return Vector3::new(vec.x, -vec.y, -vec.z);
+}
+
+/// Quaternion from [x, y, z, w].
+pub fn from_array(q: [f32; 4]) -> Quaternion {
+    return Quaternion { x: q[0], y: q[1], z: q[2], w: q[3] };
+}
+
+/// Rotates a vector by a unit quaternion.
+pub fn rotate(q: Quaternion, vec: Vector3) -> Vector3 {
+    let axis = Vector3::new(q.x, q.y, q.z);
+    let t = axis.cross(vec) * 2.0;
+    return vec + t * q.w + axis.cross(t);
+}
+
+pub fn conjugate(q: Quaternion) -> Quaternion {
+    return Quaternion { x: -q.x, y: -q.y, z: -q.z, w: q.w };
+}
+
+pub fn normalize(q: Quaternion) -> Quaternion {
+    let length = (q.x * q.x + q.y * q.y + q.z * q.z + q.w * q.w).sqrt();
+    return Quaternion { x: q.x / length, y: q.y / length, z: q.z / length, w: q.w / length };
+}
+
+/// Euler angles in radians for the axes of the rotation order.
+/// Returns [X, Y, Z] angles in axis order, independent of the rotation order.
+pub fn to_euler(q: Quaternion, order: RotationOrder) -> [f32; 3] {
+    // rotation matrix indexed by row and column, the columns are the rotated axes
+    let columns = [Vector3::X, Vector3::Y, Vector3::Z].map(|x| rotate(q, x));
+    let m = |row: usize, column: usize| [columns[column].x, columns[column].y, columns[column].z][row];
+    let [i, j, k] = order.axes();
+    // even permutations of xyz
+    let sign = if (j + 3 - i) % 3 == 1 { 1.0 } else { -1.0 };
+    let mut angles = [0.0f32; 3];
+    angles[j] = (sign * m(i, k)).clamp(-1.0, 1.0).asin();
+    angles[i] = (-sign * m(j, k)).atan2(m(k, k));
+    angles[k] = (-sign * m(i, j)).atan2(m(i, i));
+    return angles;
+}
+
+/// Quaternion from euler angles [X, Y, Z] composed in the rotation order.
+pub fn from_euler(angles: [f32; 3], order: RotationOrder) -> Quaternion {
+    let mut q = Quaternion::IDENTITY;
+    for axis in order.axes().iter() {
+        q *= axis_rotation(*axis, angles[*axis]);
+    }
+    return q;
+}
+
+/// Rotation about the x, y or z-axis for the axis index 0, 1 or 2.
+pub fn axis_rotation(axis: usize, angle: f32) -> Quaternion {
+    match axis {
+        0 => return Quaternion::from_rotation_x(angle),
+        1 => return Quaternion::from_rotation_y(angle),
+        _ => return Quaternion::from_rotation_z(angle),
+    }
+}
+
+/// Euler angles closest to the previous angles, avoids jumps of 360° and flips to the equivalent solution.
+pub fn unroll_euler(previous: [f32; 3], angles: [f32; 3], order: RotationOrder) -> [f32; 3] {
+    let [i, j, k] = order.axes();
+    let pi = std::f32::consts::PI;
+    let mut flipped = angles;
+    flipped[i] += pi;
+    flipped[j] = pi - angles[j];
+    flipped[k] += pi;
+
+    let mut best = angles;
+    let mut best_distance = f32::INFINITY;
+    for mut candidate in [angles, flipped] {
+        for axis in 0..3 {
+            let turns = ((previous[axis] - candidate[axis]) / (2.0 * pi)).round();
+            candidate[axis] += turns * 2.0 * pi;
+        }
+        let distance: f32 = (0..3).map(|x| (candidate[x] - previous[x]).abs()).sum();
+        if distance < best_distance {
+            best = candidate;
+            best_distance = distance;
+        }
+    }
+    return best;
+}
+
+/// Rotation which points the z-axis to forward and the y-axis towards up.
+fn look_rotation(forward: Vector3, up: Vector3) -> Quaternion {
+    let z = forward.normalize();
+    let x = up.cross(z).normalize();
+    let y = z.cross(x);
+    return Quaternion::from_rotation_axes(x, y, z);
+}
+
+fn finite_or_identity(q: Quaternion) -> Quaternion {
+    if q.is_finite() { q } else { Quaternion::IDENTITY }
+}
+
+#[cfg(test)]
+mod test {
+    use cgt_math::{Quaternion, Vector3};
+    use super::RotationOrder;
+
+    /// Quaternions q and -q describe the same rotation.
+    fn same_rotation(a: Quaternion, b: Quaternion) -> bool {
+        let dot = a.x * b.x + a.y * b.y + a.z * b.z + a.w * b.w;
+        return (dot.abs() - 1.0).abs() < 1e-5;
+    }
+
+    #[test]
+    fn impl_test() {
+        // euler conversion round trips in every order
+        let q = super::normalize(super::from_array([0.1, 0.2, 0.3, 0.9]));
+        for order in [RotationOrder::XYZ, RotationOrder::XZY, RotationOrder::YXZ,
+                      RotationOrder::YZX, RotationOrder::ZXY, RotationOrder::ZYX] {
+            let restored = super::from_euler(super::to_euler(q, order), order);
+            assert!(same_rotation(q, restored));
+
+            // unrolled angles stay close to the previous frame and describe the same rotation
+            let previous = [3.0, 0.1, -3.0];
+            let unrolled = super::unroll_euler(previous, super::to_euler(q, order), order);
+            assert!(same_rotation(q, super::from_euler(unrolled, order)));
+            assert!((0..3).all(|i| (unrolled[i] - previous[i]).abs() <= std::f32::consts::PI + 1e-4));
+        }
+
+        assert_eq!("zxy".parse::<RotationOrder>(), Ok(RotationOrder::ZXY));
+        assert!("XXY".parse::<RotationOrder>().is_err());
+
+        // head-local rotations act on the swapped y and z axes
+        let v = Vector3::new(0.3, -0.4, 0.8);
+        let swap = |v: Vector3| Vector3::new(v.x, v.z, v.y);
+        let expected = swap(super::rotate(q, v));
+        let rotated = super::rotate(super::from_head_local(q), swap(v));
+        assert!(rotated.distance_to(expected) < 1e-5);
+
+        // frames without detection keep the rest pose
+        let frames = vec![super::Frame::default(); 3];
+        let animation = super::animate(&frames, 30.0, true);
+        assert_eq!(animation.frame_count(), 3);
+        assert_eq!(animation.skeleton.bones.len(), 14 + 30);
+        assert_eq!(animation.skeleton.index("index_02.L"), Some(animation.skeleton.index("index_01.L").unwrap() + 1));
+        for rotation in animation.rotations[2].iter() {
+            assert_eq!(rotation.to_array(), Quaternion::IDENTITY.to_array());
+        }
+
+        // the thumb opposition twists the first thumb bone around its axis
+        let mut hand = crate::hand::test::synthetic_hand(0.5);
+        hand[3][2] -= 0.01;
+        hand[4][2] -= 0.02;
+        let frames = vec![super::Frame { right_hand: hand.clone(), ..super::Frame::default() }];
+        let animation = super::animate(&frames, 30.0, true);
+        let thumb = animation.rotations[0][animation.skeleton.index("thumb_01.R").unwrap()];
+        let angles = crate::hand::angles(&hand);
+        let twist = crate::hand::thumb_opposition(&hand);
+        let flexion_spread = Quaternion::from_rotation_z(angles[1][0]) * Quaternion::from_rotation_y(-angles[1][1]);
+        let twisted = super::conjugate(flexion_spread) * thumb;
+        assert!(twist.abs() > 0.1);
+        assert!((twisted.x + (twist / 2.0).sin()).abs() < 1e-5 && twisted.y.abs() < 1e-5 && twisted.z.abs() < 1e-5);
+    }
+}
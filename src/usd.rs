@@ -0,0 +1,100 @@
+extern crate cgt_math;
+use cgt_math::Vector3;
+use crate::skeleton::{Animation, Skeleton};
+use std::fmt::Write;
+
+/// Writes the animation as UsdSkel Skeleton with a SkelAnimation in the usda text format.
+/// Joint paths use the bone names with dots replaced by underscores, as dots are not allowed in prim names.
+/// Translations are the rest offsets and the root position per frame, time codes are frame numbers.
+pub fn write(animation: &Animation, scale: f32) -> String {
+    let bones = &animation.skeleton.bones;
+    let frame_count = animation.frame_count();
+    let fps = (1000.0 / animation.frame_time).round() / 1000.0;
+    let joints: Vec<String> = joint_paths(&animation.skeleton).iter().map(|x| format!("\"{}\"", x)).collect();
+    let joints = joints.join(", ");
+
+    let mut text = String::from("#usda 1.0\n(\n");
+    text.push_str("    defaultPrim = \"Root\"\n    metersPerUnit = 1\n    upAxis = \"Y\"\n");
+    writeln!(text, "    startTimeCode = 0\n    endTimeCode = {}", frame_count.saturating_sub(1)).unwrap();
+    writeln!(text, "    timeCodesPerSecond = {}\n    framesPerSecond = {}\n)\n", fps, fps).unwrap();
+
+    // rest transforms are local, bind transforms are in world space, the rest pose has no rotations
+    let mut rest: Vec<Vector3> = Vec::with_capacity(bones.len());
+    for bone in bones.iter() {
+        let parent = bone.parent.map(|p| rest[p]).unwrap_or(Vector3::ZERO);
+        rest.push(parent + bone.offset);
+    }
+    let rest_transforms: Vec<String> = bones.iter().map(|x| matrix(x.offset, scale)).collect();
+    let bind_transforms: Vec<String> = rest.iter().map(|x| matrix(*x, scale)).collect();
+
+    text.push_str("def SkelRoot \"Root\"\n{\n");
+    text.push_str("    def Skeleton \"Skeleton\" (\n        prepend apiSchemas = [\"SkelBindingAPI\"]\n    )\n    {\n");
+    writeln!(text, "        uniform token[] joints = [{}]", joints).unwrap();
+    writeln!(text, "        uniform matrix4d[] bindTransforms = [{}]", bind_transforms.join(", ")).unwrap();
+    writeln!(text, "        uniform matrix4d[] restTransforms = [{}]", rest_transforms.join(", ")).unwrap();
+    text.push_str("        rel skel:animationSource = </Root/Skeleton/Animation>\n\n");
+
+    text.push_str("        def SkelAnimation \"Animation\"\n        {\n");
+    writeln!(text, "            uniform token[] joints = [{}]", joints).unwrap();
+    text.push_str("            float3[] translations.timeSamples = {\n");
+    for frame in 0..frame_count {
+        let translations: Vec<String> = bones.iter()
+            .map(|x| if x.parent.is_none() { animation.root_positions[frame] } else { x.offset })
+            .map(|x| format!("({}, {}, {})", x.x * scale, x.y * scale, x.z * scale))
+            .collect();
+        writeln!(text, "                {}: [{}],", frame, translations.join(", ")).unwrap();
+    }
+    text.push_str("            }\n");
+    // usd quaternions are written as (real, i, j, k)
+    text.push_str("            quatf[] rotations.timeSamples = {\n");
+    for (frame, rotations) in animation.rotations.iter().enumerate() {
+        let rotations: Vec<String> = rotations.iter().map(|q| format!("({}, {}, {}, {})", q.w, q.x, q.y, q.z)).collect();
+        writeln!(text, "                {}: [{}],", frame, rotations.join(", ")).unwrap();
+    }
+    text.push_str("            }\n");
+    let scales = vec!["(1, 1, 1)"; bones.len()];
+    writeln!(text, "            half3[] scales = [{}]", scales.join(", ")).unwrap();
+    text.push_str("        }\n    }\n}\n");
+    return text;
+}
+
+/// Joint paths from the root to every bone.
+pub fn joint_paths(skeleton: &Skeleton) -> Vec<String> {
+    let mut paths: Vec<String> = Vec::with_capacity(skeleton.bones.len());
+    for bone in skeleton.bones.iter() {
+        let name = bone.name.replace('.', "_");
+        match bone.parent {
+            Some(p) => paths.push(format!("{}/{}", paths[p], name)),
+            None => paths.push(name),
+        }
+    }
+    return paths;
+}
+
+/// Row major matrix with the translation in the last row.
+fn matrix(translation: Vector3, scale: f32) -> String {
+    let [x, y, z] = [translation.x, translation.y, translation.z].map(|v| v * scale);
+    return format!("( (1, 0, 0, 0), (0, 1, 0, 0), (0, 0, 1, 0), ({}, {}, {}, 1) )", x, y, z);
+}
+
+#[cfg(test)]
+mod test {
+    use crate::skeleton;
+    #[test]
+    fn impl_test() {
+        let frames = vec![skeleton::Frame::default(); 3];
+        let animation = skeleton::animate(&frames, 24.0, true);
+        let paths = super::joint_paths(&animation.skeleton);
+        assert_eq!(paths[0], "hips");
+        assert!(paths.contains(&String::from("hips/chest/upper_arm_L/forearm_L/hand_L/index_01_L")));
+
+        let text = super::write(&animation, 1.0);
+        assert!(text.starts_with("#usda 1.0\n"));
+        assert!(text.contains("timeCodesPerSecond = 24\n"));
+        assert!(text.contains("endTimeCode = 2\n"));
+        assert!(text.contains("rel skel:animationSource = </Root/Skeleton/Animation>"));
+        // identity rotations as (w, x, y, z)
+        assert!(text.contains("2: [(1, 0, 0, 0), "));
+        assert_eq!(text.matches('{').count(), text.matches('}').count());
+    }
+}
@@ -26,8 +26,8 @@ def check_hand(r):
         assert all([True if not isnan(x) else False  for x in r[idx]])
 
 def check_face(r):
-    assert len(r) == 4, "Face data should be length of 4, input length has been {}".format(len(r))
-    for x in r:
+    assert len(r) == 6, "Face data should be length of 6, input length has been {}".format(len(r))
+    for x in r[:4]:
         assert (all([not isnan(i) for i in x]))
 
 def check_is_nan(r):
@@ -62,10 +62,92 @@ def test_face():
     r = mpr.face(face_data)
     check_face(r)
 
+    # eye rotations require iris landmarks
+    check_is_nan(r[4:])
+    refined_data = face_data + [[0.4790, 0.4215, 0.0250]] * 5 + [[0.5925, 0.4185, 0.0265]] * 5
+    r = mpr.face(refined_data)
+    check_face(r)
+    for x in r[4:]:
+        assert (all([not isnan(i) for i in x]))
+
+    # test facial transformation matrix
+    identity = [[1.0, 0.0, 0.0, 0.0], [0.0, 1.0, 0.0, 0.0], [0.0, 0.0, 1.0, 0.0], [0.0, 0.0, 0.0, 1.0]]
+    r = mpr.face(face_data, identity)
+    check_face(r)
+    deviation = mpr.face_matrix_deviation(face_data, identity)
+    assert 0.0 <= deviation <= 3.1416
+
     # test face nan
     r = mpr.face([])
     check_is_nan(r)
 
+def test_face_blendshapes():
+    r = mpr.face_blendshapes(face_data)
+    assert len(r) == len(mpr.BLENDSHAPES) == 52
+    assert all([0.0 <= x <= 1.0 for x in r])
+    check_is_nan([mpr.face_blendshapes([])])
+
+def test_eye_openness():
+    left, right = mpr.face_eye_openness(face_data)
+    assert 0.0 < left < 1.0 and 0.0 < right < 1.0
+    check_is_nan([mpr.face_eye_openness([])])
+
+    tracker = mpr.EyeTracker()
+    assert tracker.update(face_data) == [1.0, 1.0]
+    assert tracker.baseline() == [left, right]
+    tracker.reset()
+    check_is_nan([tracker.baseline()])
+
+def test_brows():
+    r = mpr.face_brows(face_data)
+    assert len(r) == 3
+    assert all([0.0 <= x <= 1.0 for x in r])
+    check_is_nan([mpr.face_brows([])])
+
+def test_mouth():
+    r, viseme = mpr.face_mouth(face_data)
+    assert len(r) == 5
+    assert all([0.0 <= x <= 1.0 for x in r])
+    assert viseme in mpr.VISEMES
+
+    r, viseme = mpr.face_mouth(face_data, {"jaw_rest_angle": 0.0})
+    assert viseme in mpr.VISEMES
+    r, viseme = mpr.face_mouth([])
+    check_is_nan([r])
+    assert viseme is None
+
+def test_neutral_face():
+    neutral = mpr.NeutralFace()
+    neutral.add_frame(face_data)
+    assert neutral.frames() == 1
+    check_face(neutral.rotations(face_data))
+    assert neutral.brows(face_data) == [0.0, 0.0, 0.0]
+    assert all([x == 0.0 for x in neutral.blendshapes(face_data)])
+    r, viseme = neutral.mouth(face_data)
+    assert viseme in mpr.VISEMES
+
+    restored = mpr.NeutralFace.deserialize(neutral.serialize())
+    assert restored.frames() == 1
+    assert restored.serialize() == neutral.serialize()
+
+def test_head_pose():
+    reference = [[x * 0.5 + 0.1, y * 0.5, z * 0.5] for x, y, z in face_data]
+    rotation, translation, scale, error = mpr.face_head_pose(face_data, reference, aspect_ratio=1.0)
+    assert len(rotation) == 4 and len(translation) == 3
+    assert abs(scale - 2.0) < 1e-3
+    rotation, translation, scale, error = mpr.face_head_pose(face_data)
+    assert abs(sum(x * x for x in rotation) - 1.0) < 1e-4 and scale > 0.0
+    assert mpr.face_head_pose([], reference) is None
+    assert mpr.obj_vertices("v 1.0 2.0 3.0\nf 1 2 3") == [[1.0, 2.0, 3.0]]
+
+def test_head_position():
+    translation, distance = mpr.face_head_position(face_data)
+    assert len(translation) == 3
+    assert distance > 0.0
+    _, further = mpr.face_head_position(face_data, focal_length=2.0)
+    assert further > distance
+    check_is_nan([mpr.face_head_position([])[0]])
+
 def test_holistic():
     holistic_data = [pose_data, face_data, hand_data, hand_data]
     r = mpr.holistic(holistic_data)
@@ -75,9 +157,180 @@ def test_holistic():
     check_hand(hand_l_r)
     check_hand(hand_r_r)
 
+    # test hand fallback from pose
+    holistic_data = [pose_data, face_data, [], []]
+    pose_r, face_r, hand_l_r, hand_r_r = mpr.holistic(holistic_data)
+    check_hand(hand_l_r)
+    check_hand(hand_r_r)
+    check_hand(mpr.hand_from_pose(pose_data, True))
+    check_is_nan(mpr.hand_from_pose([], False))
+
     # test holistic nan
     holistic_data = [[], [], [], []]
     r = mpr.holistic(holistic_data)
     assert len(r) == 4
     for x in r:
         check_is_nan(x)
+
+def test_hand_limited():
+    r, clamped = mpr.hand_limited(hand_data)
+    check_hand(r)
+    assert len(clamped) == 21
+
+    limits = [[0.0, 0.0, 0.0, 0.0]] * 6
+    r, clamped = mpr.hand_limited(hand_data, limits)
+    check_hand(r)
+    assert any(clamped)
+
+    r, clamped = mpr.hand_limited([])
+    check_is_nan(r)
+    assert not any(clamped)
+
+def test_hand_curls():
+    r = mpr.hand_curls(hand_data)
+    assert len(r) == 5
+    for curl, spread in r:
+        assert 0.0 <= curl <= 1.0
+        assert 0.0 <= spread <= 1.0
+    r = mpr.hand_curls(hand_data, [[0.0, 1.0, -1.0, 1.0]] * 5)
+    assert len(r) == 5
+    check_is_nan(mpr.hand_curls([]))
+
+def test_gesture():
+    r = mpr.hand_gesture(hand_data)
+    assert len(r) == 7
+    assert all([0.0 <= x <= 1.0 for x in r])
+    check_is_nan([mpr.hand_gesture([])])
+
+    tracker = mpr.GestureTracker(0.6, 2)
+    tracker.update(hand_data)
+    current = tracker.update(hand_data)
+    assert current is None or isinstance(current, str)
+    assert tracker.update([]) == current
+
+def test_export_bvh():
+    r = mpr.export_bvh([pose_data, []], fps=25.0, order="XYZ")
+    assert r.startswith("HIERARCHY\nROOT hips")
+    assert "Frames: 2\nFrame Time: 0.040000" in r
+    motion = r.strip().split("\n")[-2:]
+    assert motion[0] == motion[1]
+
+    r = mpr.export_bvh([pose_data], left_hands=[hand_data])
+    assert "JOINT index_01.L" in r
+    try:
+        mpr.export_bvh([pose_data], order="ABC")
+        assert False
+    except ValueError:
+        pass
+
+def test_evaluate_bvh():
+    poses = [pose_data] * 3
+    reference = mpr.export_bvh(poses, scale=100.0)
+    mpjpe, errors, report = mpr.evaluate_bvh(poses, reference)
+    assert mpjpe < 0.1
+    assert len(errors) == 14
+    assert errors["forearm.L"][1] < 1.0
+    assert "MPJPE" in report
+    try:
+        mpr.evaluate_bvh(poses, "HIERARCHY")
+        assert False
+    except ValueError:
+        pass
+
+def test_export_gltf(tmp_path):
+    path = tmp_path / "take.gltf"
+    mpr.export_gltf(str(path), [pose_data, pose_data], left_hands=[hand_data, []])
+    gltf = json.loads(path.read_text())
+    assert gltf["buffers"][0]["uri"] == "take.bin"
+    assert (tmp_path / "take.bin").stat().st_size == gltf["buffers"][0]["byteLength"]
+    assert "index_01.L" in [x["name"] for x in gltf["nodes"]]
+
+    path = tmp_path / "take.glb"
+    mpr.export_gltf(str(path), [pose_data])
+    assert path.read_bytes()[:4] == b"glTF"
+
+def test_csv():
+    text = mpr.write_landmarks_csv([pose_data, [], pose_data], [[0.5] * 33, [], [1.0] * 33])
+    landmarks, visibility = mpr.read_landmarks_csv(text)
+    assert len(landmarks) == 3
+    assert landmarks[1] == []
+    assert len(landmarks[2]) == 33
+    assert abs(landmarks[0][11][0] - pose_data[11][0]) < 1e-6
+    assert visibility[0][0] == 0.5
+
+    r = mpr.rotations_csv(pose=[mpr.pose(pose_data)], left_hand=[mpr.hand(hand_data)], face=[mpr.face(face_data)])
+    lines = r.strip().split("\n")
+    assert lines[0] == "frame,bone,w,x,y,z"
+    bones = [x.split(",")[1] for x in lines[1:]]
+    assert "upper_arm.L" in bones and "index_01.L" in bones and "head" in bones
+    r = mpr.rotations_csv(pose=[mpr.pose(pose_data)], order="XYZ")
+    assert r.startswith("frame,bone,x,y,z\n")
+
+def test_export_collada():
+    import xml.etree.ElementTree as ElementTree
+    r = mpr.export_collada([pose_data, pose_data], right_hands=[hand_data, hand_data])
+    root = ElementTree.fromstring(r)
+    namespace = {"c": "http://www.collada.org/2005/11/COLLADASchema"}
+    joints = [x.get("name") for x in root.iterfind(".//c:node", namespace)]
+    assert "hips" in joints and "index_01.R" in joints
+    assert len(root.findall(".//c:animation", namespace)) == len(joints)
+
+def test_export_usd():
+    r = mpr.export_usd([pose_data, pose_data], faces=[face_data, face_data], fps=24.0)
+    assert r.startswith("#usda 1.0")
+    assert "timeCodesPerSecond = 24" in r
+    assert "def SkelAnimation" in r
+    assert "hips/chest/upper_arm_L" in r and "hips/chest/head" in r
+
+def test_export_maya_anim():
+    poses = [pose_data] * 5
+    r = mpr.export_maya_anim(poses, fps=24.0)
+    assert r.startswith("animVersion 1.1;")
+    assert "timeUnit film;" in r
+    assert "anim rotate.rotateX rotateX upper_arm_L" in r
+    reduced = mpr.export_maya_anim(poses, fps=24.0, tolerance=0.1)
+    assert reduced.count("linear linear") < r.count("linear linear")
+
+def test_opensim():
+    poses = [pose_data, [], pose_data]
+    trc = mpr.export_trc(poses, window=3, name="take.trc")
+    lines = trc.split("\n")
+    assert lines[0] == "PathFileType\t4\t(X/Y/Z)\ttake.trc"
+    assert "left_shoulder" in lines[3]
+    assert lines[7].split("\t")[2] == ""
+    mot = mpr.export_mot(poses)
+    assert "inDegrees=yes" in mot
+    header = mot.split("endheader\n")[1].split("\n")[0].split("\t")
+    assert header[:2] == ["time", "pelvis_tilt"]
+    assert "knee_angle_r" in header and "arm_flex_l" in header
+
+def test_export_c3d(tmp_path):
+    import struct
+    path = str(tmp_path / "take.c3d")
+    mpr.export_c3d(path, [pose_data, [], pose_data], visibility=[[0.5] * 33, [], [1.0] * 33], fps=60.0)
+    with open(path, "rb") as f:
+        data = f.read()
+    assert len(data) % 512 == 0
+    words = struct.unpack("<5H", data[:10])
+    assert words == (0x5002, 33, 25, 1, 3)
+    assert struct.unpack("<f", data[20:24])[0] == 60.0
+    assert b"left_shoulder" in data and b"knee_angle_r" in data
+
+def test_session(tmp_path):
+    path = str(tmp_path / "take.mprs")
+    writer = mpr.SessionWriter(path)
+    writer.write(0.0, pose=pose_data, right_hand=hand_data)
+    writer.write(0.04)
+    writer.flush()
+    del writer
+    writer = mpr.SessionWriter(path)
+    writer.write(0.08, pose=[x + [0.5] for x in pose_data])
+    writer.flush()
+    frames = mpr.read_session(path)
+    assert [x[0] for x in frames] == [0.0, 0.04, 0.08]
+    assert len(frames[0][1]) == 33 and frames[0][1][0][3] == 1.0
+    assert frames[0][2] is None and len(frames[0][4]) == 21
+    assert frames[1][1:] == (None, None, None, None)
+    assert abs(frames[2][1][0][3] - 0.5) < 1e-6
+    mpr.write_session(str(tmp_path / "copy.mprs"), frames)
+    assert mpr.read_session(str(tmp_path / "copy.mprs")) == frames